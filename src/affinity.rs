@@ -0,0 +1,78 @@
+use std::io;
+
+use sysinfo::Pid;
+
+/// The CPUs `pid` may run on, one flag per CPU below `cpu_count`.
+#[cfg(target_os = "linux")]
+pub fn get(pid: Pid, cpu_count: usize) -> io::Result<Vec<bool>> {
+    // SAFETY: `cpu_set_t` is a plain bit mask and the kernel writes at most its size.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(
+            pid.as_u32() as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &mut set,
+        ) != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((0..cpu_count)
+            .map(|cpu| libc::CPU_ISSET(cpu, &set))
+            .collect())
+    }
+}
+
+/// Restrict `pid` to the CPUs flagged in `cpus`, like `taskset -p`.
+#[cfg(target_os = "linux")]
+pub fn set(pid: Pid, cpus: &[bool]) -> io::Result<()> {
+    // SAFETY: as in `get`, the set is only read by the kernel.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        for (cpu, _) in cpus.iter().enumerate().filter(|(_, &allowed)| allowed) {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(
+            pid.as_u32() as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &set,
+        ) != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get(_pid: Pid, _cpu_count: usize) -> io::Result<Vec<bool>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set(_pid: Pid, _cpus: &[bool]) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Compact list of the allowed CPUs, e.g. `0-3,6`.
+pub fn format(cpus: &[bool]) -> String {
+    let mut ranges: Vec<String> = vec![];
+    let mut cpu = 0;
+    while cpu < cpus.len() {
+        if !cpus[cpu] {
+            cpu += 1;
+            continue;
+        }
+        let start = cpu;
+        while cpu + 1 < cpus.len() && cpus[cpu + 1] {
+            cpu += 1;
+        }
+        ranges.push(if start == cpu {
+            start.to_string()
+        } else {
+            format!("{start}-{cpu}")
+        });
+        cpu += 1;
+    }
+    ranges.join(",")
+}
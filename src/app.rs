@@ -1,41 +1,318 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    time::{Duration, Instant},
+};
+
 use color_eyre::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Color, Style, Stylize},
     symbols,
-    text::Line,
-    widgets::{Axis, Block, Chart, Clear, Dataset, GraphType, Paragraph, Row, Table, TableState},
+    text::{Line, Span},
+    widgets::{
+        Axis, Block, Cell, Chart, Clear, Dataset, GraphType, Paragraph, Row, Table, TableState,
+        Wrap,
+    },
     DefaultTerminal, Frame,
 };
-use sysinfo::ProcessesToUpdate;
-use tui_textarea::TextArea;
+use sysinfo::{
+    Networks, Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, UpdateKind, Users,
+};
+use tui_textarea::{CursorMove, TextArea};
+
+#[cfg(feature = "graphics")]
+use crate::graphics::{Graphics, Protocol};
+
+use crate::{
+    ascii_chart::AsciiChart,
+    cli::Args,
+    clipboard,
+    columns::{self, Column, ProcessInfo},
+    command::Command,
+    config::Config,
+    cpu_times::{BreakdownHistory, CpuTimes},
+    detail::{self, DetailView},
+    energy::{Energy, EnergyMonitor},
+    export,
+    growth::MemoryGrowth,
+    history::{self, History, Viewport},
+    i18n::Messages,
+    layout::{self, LayoutMode},
+    logins::{self, Login},
+    metrics::MetricsLog,
+    network::NetworkMonitor,
+    numa::{self, Node},
+    perf::{Perf, Timing},
+    plugin::{Panel, PanelData},
+    power::Power,
+    procfs::{self, ProcStatus},
+    rows::{self, Pins, ProcessRow, RowsKey},
+    rules::Rule,
+    sensors::Sensors,
+    snapshot::{Change, ChangeKind, Snapshot},
+    state::{self, State},
+    stuck::{self, StuckProcesses},
+    summary::Summary,
+    systemd::{self, UnitSummary},
+    theme::Theme,
+    trend::CpuTrend,
+    units,
+    watch::{ProcessHistory, Watch},
+};
+
+/// How long a notice stays in the footer.
+const NOTICE_DURATION: Duration = Duration::from_secs(10);
+
+/// A chart series: name, points and color.
+type Series<'a> = (String, &'a [(f64, f64)], Color);
+
+/// Processes matching a `killall` pattern, shown for confirmation.
+#[derive(Debug)]
+struct KillConfirmation {
+    pattern: String,
+    pids: Vec<Pid>,
+}
+
+/// What receives key presses, see [`App::focus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    KillConfirmation,
+    CommandLine,
+    Detail,
+    Search,
+    Table,
+}
 
 #[derive(Debug, Default)]
 pub struct App {
     /// Is the application running?
     running: bool,
     system: sysinfo::System,
-    cpu: Vec<(f64, f64)>,
+    /// User accounts, to resolve the `user` field of filter expressions.
+    users: Users,
+    /// Seconds since the start, the x coordinate of chart samples.
+    elapsed: f64,
+    /// The time span the charts show, panned with Left and Right.
+    viewport: Viewport,
+    cpu: History,
+    /// Last CPU time counters, to compute the breakdown of the next sample.
+    cpu_times: Option<CpuTimes>,
+    cpu_breakdown: BreakdownHistory,
+    /// Used memory in MiB.
+    memory: History,
+    networks: Networks,
+    /// Bytes per second received and sent over all interfaces.
+    network_received: History,
+    network_sent: History,
+    /// When the network counters were last refreshed, in [`Self::elapsed`] seconds.
+    networks_sampled: Option<f64>,
     table_state: TableState,
     textarea: TextArea<'static>,
     search: bool,
+    theme: Theme,
+    messages: Messages,
+    /// Widths of columns the user resized; the others use their default constraint.
+    column_widths: HashMap<Column, u16>,
+    /// Visible columns of the process table, in display order.
+    columns: Vec<Column>,
+    /// Index into [`Self::columns`] of the column that resize and sort keys act on.
+    focused_column: usize,
+    sort_column: Column,
+    sort_descending: bool,
+    /// Show processes as a parent/child tree instead of a flat list.
+    tree: bool,
+    layout: LayoutMode,
+    /// procfs details of all processes, collected only while [`Self::needs_status`].
+    statuses: HashMap<Pid, ProcStatus>,
+    /// TCP throughput sampler, reset while no column needs it.
+    network: NetworkMonitor,
+    /// Show systemd units instead of processes?
+    services: bool,
+    service_state: TableState,
+    /// systemd unit of each process, collected only while services are involved.
+    units: HashMap<Pid, String>,
+    unit_summaries: Vec<UnitSummary>,
+    /// Only show the processes of this unit.
+    unit_filter: Option<String>,
+    /// Command panels from the config.
+    panels: Vec<Panel>,
+    sensors: Sensors,
+    power: Power,
+    /// Appends metrics to a file, if asked to with `--log` or in the config.
+    metrics_log: Option<MetricsLog>,
+    /// Sends metrics to InfluxDB or Graphite, if configured.
+    #[cfg(feature = "push")]
+    pusher: Option<crate::push::Pusher>,
+    /// Logged-in users, refreshed with the processes.
+    logins: Vec<Login>,
+    /// Nominal maximum clock frequency in MHz, if known.
+    max_frequency: Option<u64>,
+    /// NUMA nodes, refreshed with the processes.
+    numa_nodes: Vec<Node>,
+    /// Raster charts, if the terminal supports them.
+    #[cfg(feature = "graphics")]
+    graphics: Option<Graphics>,
+    /// Commands to run on conditions, checked on every process refresh.
+    rules: Vec<Rule>,
+    stuck: StuckProcesses,
+    /// Processes captured with `S`, for the diff view.
+    snapshot: Option<Snapshot>,
+    /// Changes since [`Self::snapshot`] while the diff view is shown.
+    changes: Option<Vec<Change>>,
+    change_state: TableState,
+    /// Number of process refreshes so far.
+    generation: u64,
+    /// Process table rows, valid for [`Self::rows_key`].
+    rows: Vec<ProcessRow>,
+    rows_key: Option<RowsKey>,
+    summary: Summary,
+    /// Popup with details of the selected process.
+    detail: Option<DetailView>,
+    watch: Watch,
+    /// Processes given with `--pid`; only these are shown if any.
+    watched_pids: Vec<Pid>,
+    /// Memory trend of every process, to mark the ones that keep growing.
+    memory_growth: MemoryGrowth,
+    /// CPU usage at the last two refreshes, for the arrows in the CPU column.
+    cpu_trend: CpuTrend,
+    /// Power draw and App Nap of every process on macOS.
+    energy: EnergyMonitor,
+    /// Power draw and wakeups of every process at the last refresh, macOS only.
+    energies: HashMap<Pid, Energy>,
+    /// Processes shown at the top of the table, toggled with `p`.
+    pins: Pins,
+    /// Quit once all watched processes have exited.
+    exit_with_watched: bool,
+    /// Message shown in the footer, with the time it appeared.
+    notice: Option<(String, Instant)>,
+    /// The `:` command line, while open.
+    command_line: Option<TextArea<'static>>,
+    /// Processes waiting for confirmation before being killed.
+    kill_confirmation: Option<KillConfirmation>,
+    /// Is the key binding overview open?
+    help: bool,
+    /// Timings of ratatop itself.
+    perf: Perf,
+    /// Is the performance overlay shown, toggled with `D` or `--debug`?
+    debug: bool,
+    /// Inner width of the process table at the last draw.
+    table_width: u16,
+    /// Previous search queries, oldest first.
+    search_history: Vec<String>,
+    /// Position in [`Self::search_history`] while recalling with Up/Down.
+    history_index: Option<usize>,
+    /// What was typed before recalling started, restored when moving past the newest entry.
+    history_draft: String,
+    /// The filter when the search box was opened, restored when the search is cancelled.
+    search_before: String,
+    /// The saved state under the `--profile` overrides. Only what profiles do not set is
+    /// saved over it, so a profile never replaces the default view.
+    saved_state: Option<State>,
 }
 
 impl App {
-    /// Construct a new instance of [`App`].
-    pub fn new() -> Self {
+    /// Construct a new instance of [`App`]. `saved_state` is the saved state when `state`
+    /// has a profile applied to it.
+    pub fn new(
+        config: Config,
+        args: Args,
+        state: State,
+        saved_state: Option<State>,
+        theme: Theme,
+    ) -> Self {
+        let messages = Messages::resolve(config.locale.as_deref());
+        let mut log = config.log.clone();
+        if let Some(path) = &args.log {
+            log.path = Some(path.clone());
+        }
         Self {
             running: true,
             system: sysinfo::System::new_all(),
-            cpu: vec![],
+            users: Users::new_with_refreshed_list(),
+            elapsed: 0.0,
+            viewport: Viewport::default(),
+            cpu: History::new(config.cpu_history),
+            cpu_times: None,
+            cpu_breakdown: BreakdownHistory::new(config.cpu_history),
+            memory: History::new(config.memory_history),
+            networks: Networks::new_with_refreshed_list(),
+            network_received: History::new(config.network_history),
+            network_sent: History::new(config.network_history),
+            networks_sampled: None,
             table_state: TableState::default(),
             textarea: {
-                let mut textarea = TextArea::default();
-                textarea.set_block(Block::bordered().title("Search"));
+                let mut textarea = TextArea::new(vec![state.filter]);
+                textarea.move_cursor(CursorMove::End);
+                textarea.set_block(theme.block().title(messages.search));
                 textarea
             },
             search: false,
+            theme,
+            messages,
+            column_widths: state.column_widths,
+            columns: state.columns,
+            focused_column: 0,
+            sort_column: state.sort_column,
+            sort_descending: state.sort_descending,
+            tree: state.tree,
+            layout: state.layout,
+            statuses: HashMap::new(),
+            network: NetworkMonitor::default(),
+            services: false,
+            service_state: TableState::default().with_selected(Some(0)),
+            units: HashMap::new(),
+            unit_summaries: vec![],
+            unit_filter: None,
+            panels: config.panels.into_iter().map(Panel::spawn).collect(),
+            sensors: Sensors::new(config.sensor_history),
+            power: Power::new(config.sensor_history),
+            logins: vec![],
+            metrics_log: MetricsLog::new(&log),
+            #[cfg(feature = "push")]
+            pusher: config.push.clone().map(crate::push::Pusher::spawn),
+            max_frequency: procfs::max_frequency(),
+            numa_nodes: numa::nodes(),
+            #[cfg(feature = "graphics")]
+            graphics: args
+                .graphics
+                .or_else(Protocol::detect)
+                .filter(|_| !theme.plain && !theme.ascii)
+                .and_then(Graphics::new),
+            rules: config.rules.into_iter().map(Rule::new).collect(),
+            stuck: StuckProcesses::new(config.uninterruptible_alert),
+            snapshot: None,
+            changes: None,
+            change_state: TableState::default().with_selected(Some(0)),
+            generation: 0,
+            rows: vec![],
+            rows_key: None,
+            summary: Summary::default(),
+            detail: None,
+            watch: Watch::new(&args.pids, config.watch_history),
+            watched_pids: args.pids,
+            memory_growth: MemoryGrowth::default(),
+            cpu_trend: CpuTrend::default(),
+            energy: EnergyMonitor::default(),
+            energies: HashMap::new(),
+            pins: Pins {
+                pids: vec![],
+                names: config.pinned_names.clone(),
+            },
+            exit_with_watched: args.exit_with_watched,
+            notice: None,
+            command_line: None,
+            kill_confirmation: None,
+            help: false,
+            perf: Perf::default(),
+            debug: args.debug,
+            table_width: 0,
+            search_history: state.search_history,
+            history_index: None,
+            history_draft: String::new(),
+            search_before: String::new(),
+            saved_state,
         }
     }
 
@@ -43,19 +320,197 @@ impl App {
     pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
         self.running = true;
         self.table_state.select(Some(0));
+        let started = Instant::now();
         while self.running {
+            let frame_started = Instant::now();
+            self.elapsed = started.elapsed().as_secs_f64();
             terminal.draw(|frame| {
                 if frame.count() % 60 == 0 {
-                    self.system.refresh_processes(ProcessesToUpdate::All, true);
+                    let refresh_started = Instant::now();
+                    self.refresh_processes();
+                    self.perf.refresh.record(refresh_started.elapsed());
+                    self.sample_memory_and_network();
+                    self.sensors.sample(self.elapsed);
+                    self.power.sample(self.elapsed);
+                    self.logins = logins::logins();
+                    self.record_metrics();
+                }
+                if frame.count() % 30 == 0 {
+                    self.sample_cpu_times();
+                }
+                for panel in &mut self.panels {
+                    panel.collect(self.elapsed);
                 }
                 self.system.refresh_cpu_all();
                 self.cpu
-                    .push((frame.count() as f64, self.system.global_cpu_usage() as f64));
-                self.draw(frame)
+                    .push(self.elapsed, self.system.global_cpu_usage() as f64);
+                let draw_started = Instant::now();
+                self.draw(frame);
+                self.perf.draw.record(draw_started.elapsed());
             })?;
+            #[cfg(feature = "graphics")]
+            if let Some(graphics) = &mut self.graphics {
+                if graphics.flush(&mut std::io::stdout())? {
+                    terminal.clear()?;
+                }
+            }
             self.handle_crossterm_events()?;
+            self.perf.frame.record(frame_started.elapsed());
+        }
+        self.state().save()
+    }
+
+    fn refresh_processes(&mut self) {
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_memory()
+                .with_cpu()
+                .with_disk_usage()
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_user(UpdateKind::OnlyIfNotSet),
+        );
+        self.statuses = if self.needs_status() {
+            procfs::read_all(self.system.processes().keys().copied())
+        } else {
+            HashMap::new()
+        };
+        if self.needs_network() {
+            let rates = self.network.sample(self.system.processes().keys().copied());
+            for (pid, rate) in rates {
+                if let Some(status) = self.statuses.get_mut(&pid) {
+                    status.network = Some(rate);
+                }
+            }
+        } else {
+            self.network = NetworkMonitor::default();
+        }
+        if self.needs_numa() {
+            for (&pid, status) in &mut self.statuses {
+                status.numa_node = numa::memory_node(pid);
+            }
+        }
+        if self.needs_tty() {
+            for (&pid, status) in &mut self.statuses {
+                status.tty = procfs::tty(pid);
+            }
+        }
+        self.energies = self.energy.sample(&self.system);
+        if let Some(detail) = &mut self.detail {
+            detail.refresh(&self.system);
+        }
+        self.numa_nodes = numa::nodes();
+        if self.services || self.unit_filter.is_some() {
+            self.units = systemd::units(&self.system);
+            self.unit_summaries = systemd::summarize(&self.system, &self.units);
+        }
+        if let (Some(changes), Some(snapshot)) = (&mut self.changes, &self.snapshot) {
+            *changes = snapshot.diff(&self.system);
+        }
+        let triggers: Vec<_> = self
+            .rules
+            .iter_mut()
+            .filter_map(|rule| {
+                rule.evaluate(&self.system, &self.statuses, &self.energies, &self.users)
+            })
+            .collect();
+        for trigger in triggers {
+            self.notify(format!(
+                "{} `{}`: {}",
+                self.messages.rule_triggered, trigger.rule, trigger.pid
+            ));
+        }
+        self.memory_growth.sample(&self.system);
+        self.cpu_trend.sample(&self.system);
+        for pid in self.stuck.check(&self.system) {
+            let name = self
+                .system
+                .process(pid)
+                .map(|process| process.name().to_string_lossy().to_string())
+                .unwrap_or_default();
+            let threshold =
+                detail::format_duration(self.stuck.threshold().unwrap_or_default().as_secs());
+            self.alert(format!(
+                "{pid} ({name}) {} {threshold}",
+                self.messages.uninterruptible
+            ));
+        }
+        self.generation += 1;
+        self.summary = Summary::collect(&self.system);
+
+        for pid in self.watch.sample(&self.system, self.elapsed) {
+            self.alert(format!(
+                "{} {pid} {}",
+                self.messages.detail_title, self.messages.exited
+            ));
+        }
+        if self.exit_with_watched && self.watch.all_exited() {
+            self.quit();
+        }
+    }
+
+    /// Show `text` in the footer for a while.
+    fn notify(&mut self, text: String) {
+        self.notice = Some((text, Instant::now()));
+    }
+
+    /// [`Self::notify`] and ring the terminal bell.
+    fn alert(&mut self, text: String) {
+        self.notify(text);
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+    }
+
+    fn sample_memory_and_network(&mut self) {
+        self.system.refresh_memory();
+        self.memory.push(
+            self.elapsed,
+            self.system.used_memory() as f64 / (1024.0 * 1024.0),
+        );
+        self.networks.refresh(true);
+        if let Some(sampled) = self.networks_sampled {
+            let seconds = (self.elapsed - sampled).max(0.001);
+            let (received, sent) = self
+                .networks
+                .values()
+                .fold((0, 0), |(received, sent), data| {
+                    (received + data.received(), sent + data.transmitted())
+                });
+            self.network_received
+                .push(self.elapsed, received as f64 / seconds);
+            self.network_sent.push(self.elapsed, sent as f64 / seconds);
+        }
+        self.networks_sampled = Some(self.elapsed);
+    }
+
+    fn sample_cpu_times(&mut self) {
+        let Some(times) = CpuTimes::read() else {
+            return;
+        };
+        if let Some(breakdown) = self.cpu_times.and_then(|earlier| times.since(&earlier)) {
+            self.cpu_breakdown.push(self.elapsed, breakdown);
+        }
+        self.cpu_times = Some(times);
+    }
+
+    /// The part of the application state that is persisted between sessions.
+    fn state(&self) -> State {
+        let state = State {
+            column_widths: self.column_widths.clone(),
+            search_history: self.search_history.clone(),
+            columns: self.columns.clone(),
+            sort_column: self.sort_column,
+            sort_descending: self.sort_descending,
+            tree: self.tree,
+            filter: self.search_text().to_string(),
+            layout: self.layout,
+        };
+        match &self.saved_state {
+            Some(saved) => saved.with_session_of(state),
+            None => state,
         }
-        Ok(())
     }
 
     /// Renders the user interface.
@@ -64,85 +519,1058 @@ impl App {
     /// - <https://docs.rs/ratatui/latest/ratatui/widgets/index.html>
     /// - <https://github.com/ratatui/ratatui/tree/master/examples>
     fn draw(&mut self, frame: &mut Frame) {
-        let [top, second, third] = Layout::vertical([
-            Constraint::Percentage((25)),
-            Constraint::Fill((1)),
-            Constraint::Fill((1)),
-        ])
-        .areas(frame.area());
-
-        let [left, right] =
-            Layout::horizontal([Constraint::Percentage((50)), Constraint::Percentage((50))])
-                .areas(second);
-
-        let datasets = vec![
-            // Scatter chart
-            Dataset::default()
-                .name("data1")
-                .marker(symbols::Marker::Braille)
-                .graph_type(GraphType::Line)
-                .style(Style::default().cyan())
-                .data(&self.cpu),
+        self.perf.rows = 0;
+        let [main, footer] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let areas = self.layout.areas(main);
+        if let Some(area) = areas.chart {
+            self.render_cpu_chart(frame, area);
+        }
+        if let Some(area) = areas.panels {
+            let halves = |area: Rect| {
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas::<2>(area)
+            };
+            if self.watch.is_active() {
+                let [left, right] = halves(area);
+                self.render_watch_charts(frame, left, right);
+            } else {
+                // Command panels go below the built-in ones rather than replacing them.
+                let built_in = if self.panels.is_empty() {
+                    area
+                } else {
+                    let [built_in, custom] =
+                        Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(area);
+                    self.render_panels(frame, custom);
+                    built_in
+                };
+                let [left, right] = halves(built_in);
+                if self.logins.is_empty() {
+                    self.render_cores(frame, left);
+                } else {
+                    // Borders and header around the sessions, at most half of the column.
+                    let height = (self.logins.len() as u16 + 3).min(left.height / 2);
+                    let [cores, logins] =
+                        Layout::vertical([Constraint::Fill(1), Constraint::Length(height)])
+                            .areas(left);
+                    self.render_cores(frame, cores);
+                    self.render_logins(frame, logins);
+                }
+                if self.power.zones.is_empty() {
+                    self.render_sensors(frame, right);
+                } else {
+                    let [sensors, power] =
+                        Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(right);
+                    self.render_sensors(frame, sensors);
+                    self.render_power(frame, power);
+                }
+            }
+        }
+        if self.changes.is_some() {
+            self.render_changes(frame, areas.processes);
+        } else if self.services {
+            self.render_services(frame, areas.processes);
+        } else {
+            self.render_processes(frame, areas.processes);
+        }
+        self.render_footer(frame, footer);
+
+        if self.search {
+            self.render_search(frame, areas.processes);
+        }
+        if let Some(detail) = &mut self.detail {
+            detail.render(frame, &self.system, &self.messages, &self.theme);
+        }
+        if let Some(command_line) = &self.command_line {
+            let area = Rect {
+                y: footer.y.saturating_sub(2),
+                height: 3,
+                ..footer
+            };
+            frame.render_widget(Clear, area);
+            frame.render_widget(command_line, area);
+        }
+        if let Some(confirmation) = &self.kill_confirmation {
+            self.render_kill_confirmation(frame, confirmation);
+        }
+        if self.help {
+            self.render_help(frame);
+        }
+        if self.debug {
+            self.render_perf(frame);
+        }
+    }
+
+    fn render_cpu_chart(&self, frame: &mut Frame<'_>, area: Rect) {
+        let names = [
+            self.messages.cpu_user,
+            self.messages.cpu_system,
+            self.messages.cpu_iowait,
+            self.messages.cpu_steal,
         ];
+        if self.theme.plain {
+            let mut below = 0.0;
+            let shares: Vec<_> = names
+                .iter()
+                .zip(&self.cpu_breakdown.series)
+                .filter_map(|(name, series)| {
+                    let stacked = series.current()?;
+                    let share = stacked - below;
+                    below = stacked;
+                    Some(format!("{name} {share:.1}%"))
+                })
+                .collect();
+            let lines = vec![
+                Line::from(format!(
+                    "{}:{}",
+                    self.messages.cpu_total,
+                    self.legend(&self.cpu, "%")
+                )),
+                Line::from(shares.join("  ")),
+            ];
+            let readout = Paragraph::new(lines).block(self.theme.block().title(self.messages.cpu));
+            frame.render_widget(readout, area);
+            return;
+        }
+        // The breakdown series are stacked, so the topmost is drawn first.
+        let mut series: Vec<_> = self
+            .cpu_breakdown
+            .series
+            .iter()
+            .zip(names)
+            .zip(self.theme.cpu_breakdown)
+            .rev()
+            .filter(|((series, _), _)| !series.is_empty())
+            .map(|((series, name), color)| (name.to_string(), series.points(), color))
+            .collect();
+        series.push((
+            self.messages.cpu_total.to_string(),
+            self.cpu.points(),
+            self.theme.accent,
+        ));
+        let block = self
+            .theme
+            .block()
+            .title(self.messages.cpu)
+            .title(Line::from(self.legend(&self.cpu, "%")).right_aligned());
+        self.render_chart(
+            frame,
+            area,
+            block,
+            series,
+            self.cpu.x_bounds(),
+            [0.0, 100.0],
+            None,
+        );
+    }
+
+    /// A line chart of `series`, given as name, points and color. Drawn with ASCII
+    /// characters in `--ascii` mode.
+    #[allow(clippy::too_many_arguments)]
+    fn render_chart(
+        &self,
+        frame: &mut Frame<'_>,
+        area: Rect,
+        block: Block<'_>,
+        series: Vec<Series>,
+        x_bounds: [f64; 2],
+        y_bounds: [f64; 2],
+        y_labels: Option<[String; 2]>,
+    ) {
+        let x_bounds = self.viewport.apply(x_bounds);
+        let series: Vec<Series> = series
+            .into_iter()
+            .map(|(name, points, color)| (name, history::visible(points, x_bounds), color))
+            .collect();
+        let block = match self.viewport.behind(self.elapsed) {
+            Some(behind) => block.title_bottom(
+                Line::from(format!(" -{} ", detail::format_duration(behind as u64)))
+                    .right_aligned(),
+            ),
+            None => block,
+        };
+        #[cfg(feature = "graphics")]
+        if let Some(graphics) = &self.graphics {
+            let legend: Vec<Span> = series
+                .iter()
+                .filter(|(name, _, _)| !name.is_empty())
+                .map(|(name, _, color)| Span::from(format!(" {name} ")).fg(*color))
+                .collect();
+            let block = block.title_bottom(Line::from(legend));
+            let inner = block.inner(area);
+            frame.render_widget(block, area);
+            let series: Vec<_> = series
+                .iter()
+                .map(|&(_, points, color)| (points, color))
+                .collect();
+            graphics.chart(inner, &series, x_bounds, y_bounds);
+            return;
+        }
+        if self.theme.ascii {
+            let chart = series.into_iter().fold(
+                AsciiChart::new(x_bounds, y_bounds).block(block),
+                |chart, (name, points, color)| {
+                    chart.series(name, points, Style::default().fg(color))
+                },
+            );
+            frame.render_widget(chart, area);
+            return;
+        }
+        let datasets = series
+            .into_iter()
+            .map(|(name, points, color)| {
+                Dataset::default()
+                    .name(name)
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(color))
+                    .data(points)
+            })
+            .collect();
         let x_axis = Axis::default()
-            .bounds([0f64, self.cpu.len() as f64])
-            .style(Style::default().cyan());
-        let y_axis = Axis::default()
-            .bounds([0f64, 100f64])
-            .style(Style::default().cyan());
+            .bounds(x_bounds)
+            .style(Style::default().fg(self.theme.accent));
+        let mut y_axis = Axis::default()
+            .bounds(y_bounds)
+            .style(Style::default().fg(self.theme.accent));
+        if let Some(labels) = y_labels {
+            y_axis = y_axis.labels(labels);
+        }
         let chart = Chart::new(datasets)
-            .block(Block::bordered().title("CPU"))
+            .block(block)
             .x_axis(x_axis)
-            .y_axis(y_axis);
+            .y_axis(y_axis)
+            .hidden_legend_constraints((Constraint::Ratio(1, 3), Constraint::Ratio(1, 1)));
+        frame.render_widget(chart, area);
+    }
 
-        frame.render_widget(Block::bordered(), left);
-        frame.render_widget(Block::bordered(), right);
+    /// Latest, average and peak value of `history`, as reading them off a chart is guesswork.
+    fn legend(&self, history: &History, unit: &str) -> String {
+        let value =
+            |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.1}{unit}"));
+        format!(
+            " {} {}  {} {}  {} {} ",
+            self.messages.chart_now,
+            value(history.current()),
+            self.messages.chart_average,
+            value(history.average()),
+            self.messages.chart_peak,
+            value(history.peak()),
+        )
+    }
 
-        frame.render_widget(chart, top);
-        //frame.render_widget(Block::bordered(), second);
-        //
-        self.render_processes(frame, third);
+    /// The command panels from the config, side by side.
+    fn render_panels(&self, frame: &mut Frame<'_>, area: Rect) {
+        let areas = Layout::horizontal(vec![Constraint::Fill(1); self.panels.len()]).split(area);
+        for (panel, &area) in self.panels.iter().zip(areas.iter()) {
+            let block = self.theme.block().title(panel.config.name.clone());
+            match &panel.data {
+                PanelData::Pending => frame.render_widget(block, area),
+                PanelData::Error(err) => frame.render_widget(
+                    Paragraph::new(err.as_str())
+                        .fg(self.theme.alert)
+                        .wrap(Wrap { trim: true })
+                        .block(block),
+                    area,
+                ),
+                PanelData::Table(rows) => {
+                    let columns = rows.iter().map(Vec::len).max().unwrap_or(1);
+                    let rows = rows.iter().map(|cells| Row::new(cells.clone()));
+                    let table = Table::new(rows, vec![Constraint::Fill(1); columns]).block(block);
+                    frame.render_widget(table, area);
+                }
+                PanelData::Chart if self.theme.plain => {
+                    let readout = Paragraph::new(self.legend(&panel.history, "")).block(block);
+                    frame.render_widget(readout, area);
+                }
+                PanelData::Chart => {
+                    let peak = panel.history.peak().unwrap_or_default().max(1.0);
+                    let block =
+                        block.title(Line::from(self.legend(&panel.history, "")).right_aligned());
+                    let series = vec![(String::new(), panel.history.points(), self.theme.accent)];
+                    self.render_chart(
+                        frame,
+                        area,
+                        block,
+                        series,
+                        panel.history.x_bounds(),
+                        [0.0, peak * 1.1],
+                        Some([String::from("0"), format!("{peak:.0}")]),
+                    );
+                }
+            }
+        }
+    }
 
-        if self.search {
-            self.render_search(frame, third);
+    /// Utilization and clock frequency of each core, in as many columns as needed. On
+    /// machines with several NUMA nodes, the cores are grouped by node under a line with the
+    /// memory usage of the node.
+    fn render_cores(&self, frame: &mut Frame<'_>, area: Rect) {
+        let mut block = self.theme.block().title(self.messages.cores);
+        if let Some(max) = self.max_frequency {
+            block = block.title(
+                Line::from(format!(" {} {max} MHz ", self.messages.max_frequency)).right_aligned(),
+            );
+        }
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let cpus = self.system.cpus();
+        if cpus.is_empty() || inner.height == 0 {
+            return;
+        }
+        // A node header, or the index of a core.
+        let entries: Vec<Result<&Node, usize>> = if self.numa_nodes.len() > 1 {
+            self.numa_nodes
+                .iter()
+                .flat_map(|node| {
+                    std::iter::once(Ok(node)).chain(
+                        node.cpus
+                            .iter()
+                            .filter(|&&cpu| cpu < cpus.len())
+                            .map(|&cpu| Err(cpu)),
+                    )
+                })
+                .collect()
+        } else {
+            (0..cpus.len()).map(Err).collect()
+        };
+        let rows = inner.height as usize;
+        let columns = entries.len().div_ceil(rows);
+        let areas = Layout::horizontal(vec![Constraint::Fill(1); columns])
+            .spacing(1)
+            .split(inner);
+        for (chunk, &area) in entries.chunks(rows).zip(areas.iter()) {
+            let lines: Vec<Line> = chunk
+                .iter()
+                .map(|entry| match *entry {
+                    Ok(node) => Line::from(format!(
+                        "{} {}  {} {} / {}",
+                        self.messages.numa_node,
+                        node.id,
+                        self.messages.column_memory,
+                        units::bytes(node.used_memory),
+                        units::bytes(node.total_memory),
+                    ))
+                    .bold(),
+                    Err(index) => {
+                        let cpu = &cpus[index];
+                        let usage = cpu.cpu_usage();
+                        let label = format!("{index:>3} ");
+                        let value = format!(" {usage:>5.1}% {:>5} MHz", cpu.frequency());
+                        let width =
+                            (area.width as usize).saturating_sub(label.len() + value.len() + 2);
+                        let filled = ((usage as f64 / 100.0) * width as f64).round() as usize;
+                        Line::from(vec![
+                            Span::from(label),
+                            Span::from("["),
+                            Span::from("|".repeat(filled.min(width))).fg(self.theme.accent),
+                            Span::from(" ".repeat(width - filled.min(width))),
+                            Span::from("]"),
+                            Span::from(value),
+                        ])
+                    }
+                })
+                .collect();
+            frame.render_widget(Paragraph::new(lines), area);
         }
     }
 
-    fn render_processes(&mut self, frame: &mut Frame<'_>, area: Rect) {
-        let mut rows: Vec<_> = vec![];
-        for (pid, process) in self.system.processes() {
-            let name = process.name().to_string_lossy().to_string();
-            let cpu = process.cpu_usage();
-            let row = vec![pid.to_string(), name, cpu.to_string()];
-            rows.push(row);
-        }
-
-        rows.sort_by(|a, b| {
-            let a = a[2].parse::<f32>().unwrap_or(0.0);
-            let b = b[2].parse::<f32>().unwrap_or(0.0);
-            b.partial_cmp(&a).unwrap()
+    /// Temperature history of all sensors, with guide lines at the lowest warning and
+    /// critical thresholds, and the current fan speeds.
+    fn render_sensors(&self, frame: &mut Frame<'_>, area: Rect) {
+        let mut block = self.theme.block().title(self.messages.temperatures);
+        if !self.sensors.fans.is_empty() {
+            let fans: Vec<_> = self
+                .sensors
+                .fans
+                .iter()
+                .map(|fan| format!("{} {} rpm", fan.label, fan.rpm))
+                .collect();
+            block = block.title_bottom(format!(" {} ", fans.join("  ")));
+        }
+        if self.sensors.temperatures.is_empty() {
+            frame.render_widget(Paragraph::new(self.messages.no_sensors).block(block), area);
+            return;
+        }
+        if self.theme.plain {
+            let lines: Vec<Line> = self
+                .sensors
+                .temperatures
+                .iter()
+                .map(|sensor| {
+                    Line::from(format!(
+                        "{}:{}",
+                        sensor.label,
+                        self.legend(&sensor.history, self.theme.celsius())
+                    ))
+                })
+                .collect();
+            frame.render_widget(Paragraph::new(lines).block(block), area);
+            return;
+        }
+        let x_bounds = self.sensors.x_bounds();
+        let guide = |value: f64| [(x_bounds[0], value), (x_bounds[1], value)];
+        let warn = self.sensors.warn().map(guide);
+        let critical = self.sensors.critical().map(guide);
+        let mut series: Vec<_> = self
+            .sensors
+            .temperatures
+            .iter()
+            .zip(self.theme.series.iter().cycle())
+            .map(|(sensor, &color)| (sensor.label.clone(), sensor.history.points(), color))
+            .collect();
+        if let Some(points) = &warn {
+            series.push((
+                format!(
+                    "{} {:.0}{}",
+                    self.messages.sensor_warn,
+                    points[0].1,
+                    self.theme.celsius()
+                ),
+                points,
+                self.theme.warning,
+            ));
+        }
+        if let Some(points) = &critical {
+            series.push((
+                format!(
+                    "{} {:.0}{}",
+                    self.messages.sensor_critical,
+                    points[0].1,
+                    self.theme.celsius()
+                ),
+                points,
+                self.theme.alert,
+            ));
+        }
+        let top = [self.sensors.peak(), self.sensors.critical()]
+            .into_iter()
+            .flatten()
+            .fold(0.0, f64::max)
+            .max(1.0)
+            * 1.1;
+        self.render_chart(
+            frame,
+            area,
+            block,
+            series,
+            x_bounds,
+            [0.0, top],
+            Some([
+                String::from("0"),
+                format!("{top:.0}{}", self.theme.celsius()),
+            ]),
+        );
+    }
+
+    /// Logged-in users, to know who else is on a shared machine.
+    fn render_logins(&self, frame: &mut Frame<'_>, area: Rect) {
+        let header = Row::new([
+            self.messages.label_user,
+            self.messages.label_tty,
+            self.messages.label_from,
+            self.messages.label_login,
+        ])
+        .bold();
+        let rows = self.logins.iter().map(|login| {
+            Row::new([
+                login.user.clone(),
+                login.tty.clone(),
+                login.host.clone(),
+                logins::format_time(login.since),
+            ])
         });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Fill(1),
+                Constraint::Length(16),
+            ],
+        )
+        .header(header)
+        .block(self.theme.block().title(self.messages.logins));
+        frame.render_widget(table, area);
+    }
 
-        let text = self.textarea.lines().first().unwrap();
-        rows.retain(|row| {
-            row.iter()
-                .any(|cell| cell.to_lowercase().contains(&text.to_lowercase()))
+    /// Power draw of the RAPL zones.
+    fn render_power(&self, frame: &mut Frame<'_>, area: Rect) {
+        let block = self
+            .theme
+            .block()
+            .title(format!("{} (W)", self.messages.power));
+        if let (Some(error), None) = (&self.power.error, self.power.peak()) {
+            let error = Paragraph::new(error.as_str())
+                .fg(self.theme.alert)
+                .wrap(Wrap { trim: true })
+                .block(block);
+            frame.render_widget(error, area);
+            return;
+        }
+        if self.theme.plain {
+            let lines: Vec<Line> = self
+                .power
+                .zones
+                .iter()
+                .map(|zone| {
+                    Line::from(format!(
+                        "{}:{}",
+                        zone.label,
+                        self.legend(&zone.history, " W")
+                    ))
+                })
+                .collect();
+            frame.render_widget(Paragraph::new(lines).block(block), area);
+            return;
+        }
+        let series = self
+            .power
+            .zones
+            .iter()
+            .zip(self.theme.series.iter().cycle())
+            .map(|(zone, &color)| (zone.label.clone(), zone.history.points(), color))
+            .collect();
+        let peak = self.power.peak().unwrap_or_default().max(1.0);
+        self.render_chart(
+            frame,
+            area,
+            block,
+            series,
+            self.power.x_bounds(),
+            [0.0, peak * 1.1],
+            Some([String::from("0"), format!("{peak:.0}")]),
+        );
+    }
+
+    /// CPU and memory history of the processes given with `--pid`.
+    fn render_watch_charts(&self, frame: &mut Frame<'_>, cpu_area: Rect, memory_area: Rect) {
+        let memory_title = format!("{} (MiB)", self.messages.column_memory);
+        if self.theme.plain {
+            let readout = |series: fn(&ProcessHistory) -> &History, unit| -> Vec<Line> {
+                self.watch
+                    .history
+                    .iter()
+                    .map(|(pid, history)| {
+                        Line::from(format!("{pid}:{}", self.legend(series(history), unit)))
+                    })
+                    .collect()
+            };
+            let cpu = Paragraph::new(readout(|history| &history.cpu, "%"))
+                .block(self.theme.block().title(self.messages.cpu));
+            frame.render_widget(cpu, cpu_area);
+            let memory = Paragraph::new(readout(|history| &history.memory, ""))
+                .block(self.theme.block().title(memory_title));
+            frame.render_widget(memory, memory_area);
+            return;
+        }
+        let series = |series: fn(&ProcessHistory) -> &History| -> Vec<_> {
+            self.watch
+                .history
+                .iter()
+                .zip(self.theme.series.iter().cycle())
+                .map(|((pid, history), &color)| (pid.to_string(), series(history).points(), color))
+                .collect()
+        };
+        let block = self.theme.block().title(self.messages.cpu);
+        self.render_chart(
+            frame,
+            cpu_area,
+            block,
+            series(|history| &history.cpu),
+            self.watch.x_bounds(),
+            [0.0, 100.0],
+            None,
+        );
+
+        let peak = self.watch.peak_memory();
+        self.render_chart(
+            frame,
+            memory_area,
+            self.theme.block().title(memory_title),
+            series(|history| &history.memory),
+            self.watch.x_bounds(),
+            [0.0, peak * 1.1],
+            Some([String::from("0"), format!("{peak:.0}")]),
+        );
+    }
+
+    fn render_processes(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let key = self.rows_key();
+        if self.rows_key.as_ref() != Some(&key) {
+            let selected = self.selected_pid();
+            self.rows = rows::build(
+                &self.system,
+                &self.statuses,
+                &self.energies,
+                &self.units,
+                &self.users,
+                &key,
+            );
+            self.rows_key = Some(key);
+            // Keep the selection on the same process when rows move around.
+            if let Some(index) =
+                selected.and_then(|pid| self.rows.iter().position(|row| row.pid == pid))
+            {
+                self.table_state.select(Some(index));
+            }
+        }
+
+        // Only the rows in the viewport are rendered, so scrolling is done here rather than
+        // by the table widget: below the borders and the header.
+        let height = usize::from(area.height.saturating_sub(3)).max(1);
+        let len = self.rows.len();
+        let selected = self
+            .table_state
+            .selected()
+            .map(|index| index.min(len.saturating_sub(1)));
+        let mut offset = self.table_state.offset().min(len.saturating_sub(height));
+        if let Some(index) = selected {
+            offset = offset.clamp((index + 1).saturating_sub(height), index);
+        }
+        self.table_state.select(selected);
+        *self.table_state.offset_mut() = offset;
+
+        let visible = &self.rows[offset..(offset + height).min(len)];
+        self.perf.rows = visible.len();
+        let rows = visible
+            .iter()
+            .map(|row| match self.system.process(row.pid) {
+                Some(process) => {
+                    let pinned = row.pinned;
+                    let mut cells = row.cells(
+                        ProcessInfo {
+                            process,
+                            status: self.statuses.get(&row.pid),
+                            energy: self.energies.get(&row.pid),
+                        },
+                        &self.columns,
+                        self.theme.tree_branch(),
+                    );
+                    if let Some(index) = self.columns.iter().position(|&c| c == Column::Name) {
+                        // The state letter, so the highlight is not the only cue.
+                        if stuck::is_stuck(process) {
+                            cells[index].push_str(" D");
+                        }
+                        if self.memory_growth.is_growing(row.pid) {
+                            cells[index].push(' ');
+                            cells[index].push(self.theme.growth_marker());
+                        }
+                        if self.energy.is_napped(row.pid) {
+                            cells[index].push(' ');
+                            cells[index].push(self.theme.nap_marker());
+                        }
+                    }
+                    if let (Some(index), Some(direction)) = (
+                        self.columns.iter().position(|&c| c == Column::Cpu),
+                        self.cpu_trend.direction(row.pid),
+                    ) {
+                        cells[index].push(' ');
+                        cells[index].push(self.theme.trend_marker(direction));
+                    }
+                    let row = Row::new(cells);
+                    if stuck::is_stuck(process) {
+                        row.fg(self.theme.warning)
+                    } else if pinned {
+                        row.fg(self.theme.accent).bold()
+                    } else {
+                        row
+                    }
+                }
+                None => Row::default(),
+            });
+        let mut viewport_state =
+            TableState::default().with_selected(selected.map(|index| index - offset));
+
+        let header = self.columns.iter().enumerate().map(|(index, &column)| {
+            let mut title = column.title(&self.messages).to_string();
+            if column == self.sort_column {
+                title.push(self.theme.sort_marker(self.sort_descending));
+            }
+            let cell = Cell::from(title);
+            if index == self.focused_column {
+                cell.underlined()
+            } else {
+                cell
+            }
         });
 
+        let title = match &self.unit_filter {
+            Some(unit) => format!("{} - {unit}", self.messages.processes),
+            None => self.messages.processes.to_string(),
+        };
+        let mut block = self
+            .theme
+            .block()
+            .title(title)
+            .title_bottom(self.messages.help_hint);
+        if self.summary.zombie > 0 {
+            block = block.title(
+                Line::from(format!(
+                    " {}: {} (Z) ",
+                    self.messages.status_zombie, self.summary.zombie
+                ))
+                .fg(self.theme.alert)
+                .bold()
+                .right_aligned(),
+            );
+        }
+        self.table_width = area.width.saturating_sub(2);
+        let table = Table::new(rows, self.column_constraints())
+            .row_highlight_style(self.theme.selection_style())
+            .highlight_symbol(">>")
+            .block(block)
+            .header(Row::new(header).style(Style::default().bold()));
+
+        frame.render_stateful_widget(table, area, &mut viewport_state);
+    }
+
+    fn render_services(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let rows = self.unit_summaries.iter().map(|unit| {
+            Row::new([
+                unit.name.clone(),
+                unit.processes.to_string(),
+                format!("{:.1}", unit.cpu),
+                units::bytes(unit.memory),
+            ])
+        });
+        let header = Row::new([
+            self.messages.column_unit,
+            self.messages.column_processes,
+            self.messages.column_cpu,
+            self.messages.column_memory,
+        ]);
         let table = Table::new(
-            rows.into_iter().map(Row::new).collect::<Vec<Row>>(),
+            rows,
             [
-                Constraint::Max(10),
                 Constraint::Fill(1),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(12),
+            ],
+        )
+        .row_highlight_style(self.theme.selection_style())
+        .highlight_symbol(">>")
+        .block(
+            self.theme
+                .block()
+                .title(self.messages.services)
+                .title_bottom(self.messages.help_hint),
+        )
+        .header(header.style(Style::default().bold()));
+        frame.render_stateful_widget(table, area, &mut self.service_state);
+    }
+
+    fn render_changes(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let (Some(changes), Some(snapshot)) = (&self.changes, &self.snapshot) else {
+            return;
+        };
+        let rows = changes.iter().map(|change| {
+            let (symbol, color) = match change.kind {
+                ChangeKind::Appeared => ("+", self.theme.added),
+                ChangeKind::Disappeared => ("-", self.theme.alert),
+                ChangeKind::Changed => ("~", Color::Reset),
+            };
+            let sign = if change.memory < 0 { "-" } else { "+" };
+            Row::new([
+                symbol.to_string(),
+                change.pid.to_string(),
+                change.name.clone(),
+                format!("{:+.1}", change.cpu),
+                format!("{sign}{}", units::bytes(change.memory.unsigned_abs())),
+            ])
+            .style(Style::default().fg(color))
+        });
+        let header = Row::new([
+            "",
+            self.messages.column_pid,
+            self.messages.column_name,
+            self.messages.column_cpu,
+            self.messages.column_memory,
+        ]);
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(1),
+                Constraint::Length(8),
                 Constraint::Fill(1),
+                Constraint::Length(10),
+                Constraint::Length(12),
             ],
         )
-        .row_highlight_style(Style::default().bg(Color::DarkGray))
+        .row_highlight_style(self.theme.selection_style())
         .highlight_symbol(">>")
-        .block(Block::bordered().title("Processes"))
-        .header(Row::new(vec!["PID", "Name", "CPU"]).style(Style::default().bold()));
+        .block(
+            self.theme
+                .block()
+                .title(format!(
+                    "{} ({})",
+                    self.messages.diff_title,
+                    detail::format_duration(snapshot.taken.elapsed().as_secs())
+                ))
+                .title_bottom(self.messages.help_hint),
+        )
+        .header(header.style(Style::default().bold()));
+        frame.render_stateful_widget(table, area, &mut self.change_state);
+    }
 
-        frame.render_stateful_widget(table, area, &mut self.table_state);
+    /// Show or hide the differences to the snapshot.
+    fn toggle_changes(&mut self) {
+        if self.changes.is_some() {
+            self.changes = None;
+        } else if let Some(snapshot) = &self.snapshot {
+            self.changes = Some(snapshot.diff(&self.system));
+            self.change_state.select(Some(0));
+        } else {
+            self.notify(self.messages.no_snapshot.to_string());
+        }
+    }
+
+    /// Switch between the services view and the process table, dropping any unit filter.
+    fn toggle_services(&mut self) {
+        self.changes = None;
+        self.services = !self.services;
+        self.unit_filter = None;
+        self.refresh_processes();
+    }
+
+    /// Show the processes of the selected unit.
+    fn drill_down(&mut self) {
+        let Some(unit) = self
+            .service_state
+            .selected()
+            .and_then(|index| self.unit_summaries.get(index))
+        else {
+            return;
+        };
+        self.unit_filter = Some(unit.name.clone());
+        self.services = false;
+        self.table_state.select(Some(0));
+    }
+
+    fn render_footer(&self, frame: &mut Frame<'_>, area: Rect) {
+        if let Some((notice, _)) = &self.notice {
+            // Plain mode marks notices with text instead of color.
+            let marker = if self.theme.plain { "!" } else { "" };
+            frame.render_widget(
+                Paragraph::new(format!("{marker} {notice}"))
+                    .bold()
+                    .fg(self.theme.alert),
+                area,
+            );
+            return;
+        }
+        let messages = &self.messages;
+        let summary = &self.summary;
+        let text = format!(
+            " {}: {} {}, {} {} | {} {}, {} {}, {} {} | ratatop: {:.1}% CPU, {}",
+            messages.processes,
+            summary.total,
+            messages.summary_total,
+            self.rows.len(),
+            messages.summary_shown,
+            summary.running,
+            messages.status_running,
+            summary.sleeping,
+            messages.status_sleeping,
+            summary.zombie,
+            messages.status_zombie,
+            summary.own_cpu,
+            units::bytes(summary.own_memory),
+        );
+        frame.render_widget(Paragraph::new(text), area);
+    }
+
+    /// Move the selection to the next zombie process in the table, wrapping around.
+    fn select_next_zombie(&mut self) {
+        let start = self.table_state.selected().map_or(0, |index| index + 1);
+        let zombie = (0..self.rows.len())
+            .map(|offset| (start + offset) % self.rows.len())
+            .find(|&index| {
+                self.system
+                    .process(self.rows[index].pid)
+                    .is_some_and(|process| process.status() == ProcessStatus::Zombie)
+            });
+        match zombie {
+            Some(index) => self.table_state.select(Some(index)),
+            None => self.notify(self.messages.no_zombies.to_string()),
+        }
+    }
+
+    /// Append to the metrics log and push to the metrics server when due. Logging stops after
+    /// the first error, which is shown as a notice; pushing keeps retrying.
+    fn record_metrics(&mut self) {
+        #[cfg(feature = "push")]
+        if let Some(pusher) = &mut self.pusher {
+            pusher.push(&self.system);
+            if let Some(err) = pusher.error() {
+                self.notify(format!("{} {err}", self.messages.failed));
+            }
+        }
+        let Some(log) = &mut self.metrics_log else {
+            return;
+        };
+        if let Err(err) = log.record(&self.system) {
+            let notice = format!("{} {}: {err}", self.messages.failed, log.path().display());
+            self.metrics_log = None;
+            self.notify(notice);
+        }
+    }
+
+    /// Move the selection to the parent of the selected process.
+    fn select_parent(&mut self) {
+        let parent = self
+            .selected_pid()
+            .and_then(|pid| self.system.process(pid)?.parent());
+        match parent.and_then(|parent| self.rows.iter().position(|row| row.pid == parent)) {
+            Some(index) => self.table_state.select(Some(index)),
+            None => self.notify(self.messages.no_parent.to_string()),
+        }
+    }
+
+    /// Move the selection to the topmost child of the selected process.
+    fn select_first_child(&mut self) {
+        let Some(pid) = self.selected_pid() else {
+            return;
+        };
+        let child = self.rows.iter().position(|row| {
+            self.system
+                .process(row.pid)
+                .is_some_and(|process| process.parent() == Some(pid))
+        });
+        match child {
+            Some(index) => self.table_state.select(Some(index)),
+            None => self.notify(self.messages.no_children.to_string()),
+        }
+    }
+
+    /// Show the first hidden column right after the focused one.
+    fn add_column(&mut self) {
+        if let Some(column) = Column::all()
+            .into_iter()
+            .find(|column| !self.columns.contains(column))
+        {
+            self.focused_column = (self.focused_column + 1).min(self.columns.len());
+            self.columns.insert(self.focused_column, column);
+        }
+    }
+
+    /// Copy the main details of the selected process to the clipboard, for pasting into
+    /// chats and tickets.
+    fn copy_selected(&mut self) {
+        let Some(process) = self.selected_pid().and_then(|pid| self.system.process(pid)) else {
+            return;
+        };
+        let cmd = process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let messages = &self.messages;
+        let text = [
+            (messages.column_pid, process.pid().to_string()),
+            (
+                messages.column_name,
+                process.name().to_string_lossy().to_string(),
+            ),
+            (messages.label_command, cmd),
+            (messages.column_cpu, format!("{:.1}%", process.cpu_usage())),
+            (messages.column_memory, units::bytes(process.memory())),
+        ]
+        .map(|(label, value)| format!("{label}: {value}"))
+        .join("\n");
+        match clipboard::copy(&mut std::io::stdout(), &text) {
+            Ok(()) => self.notify(self.messages.copied.to_string()),
+            Err(err) => self.notify(err.to_string()),
+        }
+    }
+
+    /// Pin the selected process to the top of the table, or unpin it.
+    fn toggle_pin(&mut self) {
+        let Some(pid) = self.selected_pid() else {
+            return;
+        };
+        match self.pins.pids.iter().position(|&pinned| pinned == pid) {
+            Some(index) => {
+                self.pins.pids.remove(index);
+            }
+            None => self.pins.pids.push(pid),
+        }
+    }
+
+    /// Swap the focused column with its neighbour, `delta` being -1 for left or 1 for right.
+    fn move_focused_column(&mut self, delta: isize) {
+        let Some(target) = self.focused_column.checked_add_signed(delta) else {
+            return;
+        };
+        if target < self.columns.len() {
+            self.columns.swap(self.focused_column, target);
+            self.focused_column = target;
+        }
+    }
+
+    /// Are procfs details needed for the visible columns or sorting?
+    fn needs_status(&self) -> bool {
+        self.sort_column.needs_status() || self.columns.iter().any(|column| column.needs_status())
+    }
+
+    fn needs_numa(&self) -> bool {
+        self.sort_column.needs_numa() || self.columns.iter().any(|column| column.needs_numa())
+    }
+
+    fn needs_tty(&self) -> bool {
+        self.sort_column.needs_tty() || self.columns.iter().any(|column| column.needs_tty())
+    }
+
+    fn needs_network(&self) -> bool {
+        self.sort_column.needs_network() || self.columns.iter().any(|column| column.needs_network())
+    }
+
+    fn selected_pid(&self) -> Option<Pid> {
+        self.table_state
+            .selected()
+            .and_then(|index| self.rows.get(index))
+            .map(|row| row.pid)
+    }
+
+    fn rows_key(&self) -> RowsKey {
+        RowsKey {
+            generation: self.generation,
+            query: self.search_text().to_string(),
+            sort_column: self.sort_column,
+            sort_descending: self.sort_descending,
+            tree: self.tree,
+            unit: self.unit_filter.clone(),
+            pids: self.watched_pids.clone(),
+            pins: self.pins.clone(),
+        }
+    }
+
+    fn column_constraints(&self) -> Vec<Constraint> {
+        self.columns
+            .iter()
+            .map(|column| match self.column_widths.get(column) {
+                Some(&width) => Constraint::Length(width),
+                None => column.default_constraint(),
+            })
+            .collect()
+    }
+
+    /// Grow (positive `delta`) or shrink the focused column, starting from its current
+    /// on-screen width.
+    fn resize_focused_column(&mut self, delta: i16) {
+        // Mirror the table layout: highlight symbol followed by single-space gaps.
+        let area = Rect::new(0, 0, self.table_width.saturating_sub(2), 1);
+        let widths = Layout::horizontal(self.column_constraints())
+            .spacing(1)
+            .split(area);
+        let column = self.columns[self.focused_column];
+        let width = widths[self.focused_column]
+            .width
+            .saturating_add_signed(delta)
+            .clamp(columns::MIN_WIDTH, columns::MAX_WIDTH);
+        self.column_widths.insert(column, width);
     }
 
     fn render_search(&mut self, frame: &mut Frame<'_>, area: Rect) {
@@ -156,12 +1584,108 @@ impl App {
         frame.render_widget(&self.textarea, search_area);
     }
 
+    fn render_kill_confirmation(&self, frame: &mut Frame<'_>, confirmation: &KillConfirmation) {
+        let area = layout::centered(frame.area(), 60, confirmation.pids.len() as u16 + 4);
+        let rows = confirmation.pids.iter().map(|&pid| {
+            let name = self
+                .system
+                .process(pid)
+                .map(|process| process.name().to_string_lossy().to_string())
+                .unwrap_or_default();
+            Row::new([pid.to_string(), name])
+        });
+        let table = Table::new(rows, [Constraint::Length(8), Constraint::Fill(1)])
+            .header(
+                Row::new([self.messages.column_pid, self.messages.column_name])
+                    .style(Style::default().bold()),
+            )
+            .block(
+                self.theme
+                    .block()
+                    .title(format!(
+                        "{} `{}`",
+                        self.messages.kill_title, confirmation.pattern
+                    ))
+                    .title_bottom(self.messages.kill_hint),
+            );
+        frame.render_widget(Clear, area);
+        frame.render_widget(table, area);
+    }
+
+    fn render_help(&self, frame: &mut Frame<'_>) {
+        let area = layout::centered(frame.area(), 64, self.messages.help.len() as u16 + 2);
+        let rows = self
+            .messages
+            .help
+            .iter()
+            .map(|&(keys, action)| Row::new([Cell::from(keys).bold(), Cell::from(action)]));
+        let table = Table::new(rows, [Constraint::Length(12), Constraint::Fill(1)])
+            .block(self.theme.block().title(self.messages.help_title));
+        frame.render_widget(Clear, area);
+        frame.render_widget(table, area);
+    }
+
+    /// The performance overlay in the top right corner.
+    fn render_perf(&self, frame: &mut Frame<'_>) {
+        let timing = |label: &str, timing: &Timing| {
+            Row::new([
+                Cell::from(label.to_string()).bold(),
+                Cell::from(format!("{:.1} ms", timing.last().as_secs_f64() * 1000.0)),
+                Cell::from(format!("{:.1} ms", timing.average().as_secs_f64() * 1000.0)),
+                Cell::from(format!("{:.1} ms", timing.max().as_secs_f64() * 1000.0)),
+            ])
+        };
+        let rows = [
+            timing(self.messages.perf_frame, &self.perf.frame),
+            timing(self.messages.perf_draw, &self.perf.draw),
+            timing(self.messages.perf_refresh, &self.perf.refresh),
+            Row::new([
+                Cell::from(self.messages.perf_rows).bold(),
+                Cell::from(self.perf.rows.to_string()),
+            ]),
+        ];
+        let header = Row::new([
+            "",
+            self.messages.perf_last,
+            self.messages.perf_average,
+            self.messages.perf_max,
+        ])
+        .bold();
+        let width = 48.min(frame.area().width);
+        let area = Rect {
+            x: frame.area().right().saturating_sub(width),
+            y: frame.area().y,
+            width,
+            height: (rows.len() as u16 + 3).min(frame.area().height),
+        };
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(9),
+                Constraint::Length(11),
+                Constraint::Length(11),
+                Constraint::Length(11),
+            ],
+        )
+        .header(header)
+        .block(self.theme.block().title(self.messages.perf_title));
+        frame.render_widget(Clear, area);
+        frame.render_widget(table, area);
+    }
+
     /// Reads the crossterm events and updates the state of [`App`].
     ///
     /// If your application needs to perform work in between handling events, you can use the
     /// [`event::poll`] function to check if there are any events available with a timeout.
     fn handle_crossterm_events(&mut self) -> Result<()> {
-        if event::poll(std::time::Duration::from_millis(16))? {
+        if self
+            .notice
+            .as_ref()
+            .is_some_and(|(_, since)| since.elapsed() > NOTICE_DURATION)
+        {
+            self.notice = None;
+        }
+        if event::poll(Duration::from_millis(16))? {
             match event::read()? {
                 // it's important to check KeyEventKind::Press to avoid handling key release events
                 Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
@@ -173,29 +1697,337 @@ impl App {
         Ok(())
     }
 
+    /// Where key presses go: the topmost overlay, or the table when none is open.
+    fn focus(&self) -> Focus {
+        if self.kill_confirmation.is_some() {
+            Focus::KillConfirmation
+        } else if self.command_line.is_some() {
+            Focus::CommandLine
+        } else if self.detail.is_some() {
+            Focus::Detail
+        } else if self.search {
+            Focus::Search
+        } else {
+            Focus::Table
+        }
+    }
+
     /// Handles the key events and updates the state of [`App`].
+    ///
+    /// Hand the key to whatever has the focus. Global keybindings only apply to the table,
+    /// so text typed into an input never triggers them.
     fn on_key_event(&mut self, key: KeyEvent) {
-        if self.search {
-            self.textarea.input(key);
+        match self.focus() {
+            Focus::KillConfirmation => self.on_kill_confirmation_key(key),
+            Focus::CommandLine => self.on_command_line_key(key),
+            Focus::Detail => {
+                if let Some(detail) = &mut self.detail {
+                    if !detail.on_key(key, &self.system) {
+                        self.detail = None;
+                    }
+                }
+            }
+            Focus::Search => self.on_search_key(key),
+            Focus::Table => self.on_table_key(key),
+        }
+    }
+
+    fn on_kill_confirmation_key(&mut self, key: KeyEvent) {
+        if let Some(confirmation) = &self.kill_confirmation {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    let pids = confirmation.pids.clone();
+                    self.kill_confirmation = None;
+                    self.kill(&pids);
+                }
+                KeyCode::Char('n') | KeyCode::Esc => self.kill_confirmation = None,
+                _ => {}
+            }
+        }
+    }
+
+    fn on_command_line_key(&mut self, key: KeyEvent) {
+        if let Some(command_line) = &mut self.command_line {
+            match key.code {
+                KeyCode::Enter => {
+                    let text = command_line.lines()[0].clone();
+                    self.command_line = None;
+                    self.run_command(&text);
+                }
+                KeyCode::Esc => self.command_line = None,
+                _ => {
+                    command_line.input(key);
+                }
+            }
+        }
+    }
+
+    /// Keys typed into the search box. Enter applies the query as the filter and Esc restores
+    /// the previous one, both returning the focus to the table.
+    fn on_search_key(&mut self, key: KeyEvent) {
+        match (key.modifiers, key.code) {
+            (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
+            (_, KeyCode::Enter) => {
+                self.remember_search();
+                self.search = false;
+            }
+            (_, KeyCode::Esc) => {
+                self.history_index = None;
+                let before = std::mem::take(&mut self.search_before);
+                self.set_search_text(before);
+                self.search = false;
+            }
+            (_, KeyCode::Up) => self.recall_history(true),
+            (_, KeyCode::Down) => self.recall_history(false),
+            _ => {
+                self.textarea.input(key);
+            }
         }
+    }
+
+    fn on_table_key(&mut self, key: KeyEvent) {
         match (key.modifiers, key.code) {
             (_, KeyCode::Esc | KeyCode::Char('q'))
             | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
 
+            (_, KeyCode::Char('j')) if self.changes.is_some() => self.change_state.select_next(),
+            (_, KeyCode::Char('k')) if self.changes.is_some() => {
+                self.change_state.select_previous()
+            }
+            (_, KeyCode::Char('j')) if self.services => self.service_state.select_next(),
+            (_, KeyCode::Char('k')) if self.services => self.service_state.select_previous(),
+            (_, KeyCode::Enter) if self.changes.is_some() => {
+                self.detail = self
+                    .change_state
+                    .selected()
+                    .and_then(|index| self.changes.as_ref()?.get(index))
+                    .filter(|change| change.kind != ChangeKind::Disappeared)
+                    .map(|change| DetailView::new(change.pid, &self.system));
+            }
+            (_, KeyCode::Enter) if self.services => self.drill_down(),
+            (_, KeyCode::Enter) => {
+                self.detail = self
+                    .selected_pid()
+                    .map(|pid| DetailView::new(pid, &self.system));
+            }
             (_, KeyCode::Char('j')) => {
                 self.table_state.select_next();
             }
             (_, KeyCode::Char('k')) => {
                 self.table_state.select_previous();
             }
+            (_, KeyCode::Char('Z')) => self.select_next_zombie(),
+            (_, KeyCode::Left) => self.viewport.pan(-1.0, self.cpu.x_bounds()),
+            (_, KeyCode::Right) => self.viewport.pan(1.0, self.cpu.x_bounds()),
+            (_, KeyCode::End) => self.viewport.follow(),
+            (_, KeyCode::Char('P')) => self.select_parent(),
+            (_, KeyCode::Char('C')) => self.select_first_child(),
+            (_, KeyCode::Char('p')) => self.toggle_pin(),
+            (_, KeyCode::Char('y')) => self.copy_selected(),
+            (_, KeyCode::Char('u')) => self.toggle_services(),
+            (_, KeyCode::Char('S')) => {
+                self.snapshot = Some(Snapshot::capture(&self.system));
+                self.notify(self.messages.snapshot_taken.to_string());
+            }
+            (_, KeyCode::Char('d')) => self.toggle_changes(),
             (_, KeyCode::Char('s')) => {
-                self.search = !self.search;
+                self.search_before = self.search_text().to_string();
+                self.search = true;
+            }
+            (_, KeyCode::Char('c')) => self.set_search_text(String::new()),
+            (_, KeyCode::Char('[')) => {
+                self.focused_column =
+                    (self.focused_column + self.columns.len() - 1) % self.columns.len();
+            }
+            (_, KeyCode::Char(']')) => {
+                self.focused_column = (self.focused_column + 1) % self.columns.len();
+            }
+            (_, KeyCode::Char('{')) => self.move_focused_column(-1),
+            (_, KeyCode::Char('}')) => self.move_focused_column(1),
+            (_, KeyCode::Char('+')) => self.resize_focused_column(1),
+            (_, KeyCode::Char('-')) => self.resize_focused_column(-1),
+            (_, KeyCode::Char('=')) => {
+                self.column_widths
+                    .remove(&self.columns[self.focused_column]);
+            }
+            (_, KeyCode::Char('o')) => {
+                let column = self.columns[self.focused_column];
+                if column == self.sort_column {
+                    self.sort_descending = !self.sort_descending;
+                } else {
+                    self.sort_column = column;
+                }
+            }
+            (_, KeyCode::Char('t')) => self.tree = !self.tree,
+            (_, KeyCode::Char('v')) => self.layout = self.layout.next(),
+            (_, KeyCode::Char('x')) if self.columns.len() > 1 => {
+                self.columns.remove(self.focused_column);
+                self.focused_column = self.focused_column.min(self.columns.len() - 1);
+            }
+            (_, KeyCode::Char('a')) => self.add_column(),
+            (_, KeyCode::Char('X')) => {
+                self.columns = Column::DEFAULT.to_vec();
+                self.focused_column = 0;
+            }
+            (_, KeyCode::Char('?')) => self.help = !self.help,
+            (_, KeyCode::Char('D')) => self.debug = !self.debug,
+            (_, KeyCode::Char(':')) => {
+                let mut command_line = TextArea::default();
+                command_line.set_block(self.theme.block().title(":"));
+                self.command_line = Some(command_line);
             }
             // Add other key handlers here.
             _ => {}
         }
     }
 
+    fn run_command(&mut self, text: &str) {
+        match Command::parse(text) {
+            Ok(Command::GoTo(pid)) => {
+                let pid = Pid::from_u32(pid);
+                match self.rows.iter().position(|row| row.pid == pid) {
+                    Some(index) => self.table_state.select(Some(index)),
+                    None if self.system.process(pid).is_some() => {
+                        self.notify(format!("{} {pid}", self.messages.pid_hidden));
+                    }
+                    None => self.notify(format!("{} {pid}", self.messages.no_such_pid)),
+                }
+            }
+            Ok(Command::KillAll(pattern)) => {
+                let own = sysinfo::get_current_pid().ok();
+                let needle = pattern.to_lowercase();
+                let mut pids: Vec<Pid> = self
+                    .system
+                    .processes()
+                    .values()
+                    .filter(|process| Some(process.pid()) != own)
+                    .filter(|process| {
+                        process
+                            .name()
+                            .to_string_lossy()
+                            .to_lowercase()
+                            .contains(&needle)
+                    })
+                    .map(|process| process.pid())
+                    .collect();
+                if pids.is_empty() {
+                    self.notify(format!("{} `{pattern}`", self.messages.no_match));
+                    return;
+                }
+                pids.sort();
+                self.kill_confirmation = Some(KillConfirmation { pattern, pids });
+            }
+            Ok(Command::Export(path)) => {
+                let path = path.unwrap_or_else(export::default_path);
+                match export::write_csv(&path, &self.chart_series(), self.elapsed) {
+                    Ok(()) => self.notify(format!("{} {}", self.messages.exported, path.display())),
+                    Err(err) => self.notify(format!(
+                        "{} {}: {err}",
+                        self.messages.failed,
+                        path.display()
+                    )),
+                }
+            }
+            Err(err) => self.notify(err),
+        }
+    }
+
+    /// Every chart series with a name for the CSV export.
+    fn chart_series(&self) -> Vec<(String, &History)> {
+        let mut series = vec![("cpu".to_string(), &self.cpu)];
+        for (name, history) in ["user", "system", "iowait", "steal"]
+            .into_iter()
+            .zip(&self.cpu_breakdown.series)
+        {
+            // Stacked as on the chart: each value includes the series before it.
+            series.push((format!("cpu_{name}_stacked"), history));
+        }
+        series.push(("memory_used_mib".to_string(), &self.memory));
+        series.push((
+            "network_received_bytes_per_second".to_string(),
+            &self.network_received,
+        ));
+        series.push((
+            "network_sent_bytes_per_second".to_string(),
+            &self.network_sent,
+        ));
+        for (pid, history) in &self.watch.history {
+            series.push((format!("pid_{pid}_cpu"), &history.cpu));
+            series.push((format!("pid_{pid}_memory_mib"), &history.memory));
+        }
+        series
+    }
+
+    /// Send SIGTERM to `pids` and report how it went.
+    fn kill(&mut self, pids: &[Pid]) {
+        let failed = pids
+            .iter()
+            .filter(|&&pid| {
+                #[cfg(windows)]
+                let killed = crate::windows::terminate(pid).is_ok();
+                #[cfg(not(windows))]
+                let killed = self.system.process(pid).is_some_and(|process| {
+                    process
+                        .kill_with(sysinfo::Signal::Term)
+                        .unwrap_or_else(|| process.kill())
+                });
+                !killed
+            })
+            .count();
+        self.notify(format!(
+            "{}: {}, {}: {failed}",
+            self.messages.killed,
+            pids.len() - failed,
+            self.messages.failed
+        ));
+    }
+
+    /// Step through the search history, `older` moving towards the oldest entry.
+    fn recall_history(&mut self, older: bool) {
+        let len = self.search_history.len();
+        let index = match (self.history_index, older) {
+            (None, true) if len > 0 => {
+                self.history_draft = self.search_text().to_string();
+                Some(len - 1)
+            }
+            (None, _) => return,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) => Some(index + 1).filter(|&index| index < len),
+        };
+        self.history_index = index;
+        let text = match index {
+            Some(index) => self.search_history[index].clone(),
+            None => std::mem::take(&mut self.history_draft),
+        };
+        self.set_search_text(text);
+    }
+
+    /// Append the current query to the search history, moving repeated queries to the end.
+    fn remember_search(&mut self) {
+        self.history_index = None;
+        let text = self.search_text().to_string();
+        if text.is_empty() {
+            return;
+        }
+        self.search_history.retain(|entry| *entry != text);
+        self.search_history.push(text);
+        let excess = self
+            .search_history
+            .len()
+            .saturating_sub(state::MAX_SEARCH_HISTORY);
+        self.search_history.drain(..excess);
+    }
+
+    fn set_search_text(&mut self, text: String) {
+        self.textarea.move_cursor(CursorMove::Head);
+        self.textarea.delete_line_by_end();
+        self.textarea.insert_str(text);
+    }
+
+    fn search_text(&self) -> &str {
+        self.textarea.lines().first().map_or("", String::as_str)
+    }
+
     /// Set running to false to quit the application.
     fn quit(&mut self) {
         self.running = false;
@@ -0,0 +1,109 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    widgets::{Block, Widget},
+};
+
+/// Symbols of the series, in order.
+const SYMBOLS: [char; 6] = ['*', '+', 'o', 'x', '#', '%'];
+
+/// A scatter plot drawn with plain ASCII characters, for `--ascii`, where the Braille
+/// markers and axis lines of [`ratatui::widgets::Chart`] would be mangled.
+#[derive(Debug, Default)]
+pub struct AsciiChart<'a> {
+    series: Vec<Series<'a>>,
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+    block: Option<Block<'a>>,
+}
+
+#[derive(Debug)]
+struct Series<'a> {
+    name: String,
+    points: &'a [(f64, f64)],
+    style: Style,
+}
+
+impl<'a> AsciiChart<'a> {
+    pub fn new(x_bounds: [f64; 2], y_bounds: [f64; 2]) -> Self {
+        Self {
+            x_bounds,
+            y_bounds,
+            ..Self::default()
+        }
+    }
+
+    pub fn series(
+        mut self,
+        name: impl Into<String>,
+        points: &'a [(f64, f64)],
+        style: Style,
+    ) -> Self {
+        self.series.push(Series {
+            name: name.into(),
+            points,
+            style,
+        });
+        self
+    }
+
+    /// The block around the chart. A legend of the series symbols is added to its bottom.
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+impl Widget for AsciiChart<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let legend: Vec<_> = self
+            .series
+            .iter()
+            .zip(SYMBOLS.iter().cycle())
+            .filter(|(series, _)| !series.name.is_empty())
+            .map(|(series, symbol)| format!("{symbol} {}", series.name))
+            .collect();
+        let inner = match self.block {
+            Some(block) => {
+                let block = if legend.is_empty() {
+                    block
+                } else {
+                    block.title_bottom(format!(" {} ", legend.join("  ")))
+                };
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        if inner.is_empty() {
+            return;
+        }
+        let [x0, x1] = self.x_bounds;
+        let [y0, y1] = self.y_bounds;
+        let scale = |value: f64, low: f64, high: f64, size: u16| -> Option<u16> {
+            let ratio = if high > low {
+                (value - low) / (high - low)
+            } else {
+                1.0
+            };
+            (0.0..=1.0)
+                .contains(&ratio)
+                .then(|| (ratio * f64::from(size - 1)).round() as u16)
+        };
+        for (series, &symbol) in self.series.iter().zip(SYMBOLS.iter().cycle()) {
+            for &(x, y) in series.points {
+                let (Some(column), Some(row)) = (
+                    scale(x, x0, x1, inner.width),
+                    scale(y, y0, y1, inner.height),
+                ) else {
+                    continue;
+                };
+                buf[(inner.x + column, inner.bottom() - 1 - row)]
+                    .set_char(symbol)
+                    .set_style(series.style);
+            }
+        }
+    }
+}
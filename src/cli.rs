@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use color_eyre::{eyre::eyre, Result};
+use sysinfo::Pid;
+
+use crate::report::Format;
+
+const USAGE: &str = "\
+Usage: ratatop [OPTIONS]
+
+Options:
+  --profile NAME  Start with the settings of [profile.NAME] from the config file
+  --pid PID       Watch only this process; can be given several times
+  --exit          Quit once all watched processes have exited
+  --once          Print a single snapshot and exit instead of starting the interface
+  --format FORMAT Output format of --once: text (default) or json
+  --log FILE      Append system metrics to FILE while running, as JSON Lines if it ends
+                  in .jsonl and CSV otherwise
+  --no-color      No colors or charts, everything shown as text; also set by NO_COLOR
+  --ascii         Draw with ASCII characters only
+  --debug         Show frame, draw and refresh timings of ratatop itself
+  --graphics PROTOCOL
+                  Draw charts as images with kitty or sixel graphics, if built with
+                  the graphics feature; Kitty is detected automatically
+  -h, --help      Print this help
+  -V, --version   Print the version";
+
+/// Command line arguments.
+#[derive(Debug, Default)]
+pub struct Args {
+    pub profile: Option<String>,
+    /// Processes to watch, all processes are shown if empty.
+    pub pids: Vec<Pid>,
+    /// Quit once all watched processes have exited.
+    pub exit_with_watched: bool,
+    /// Print a single snapshot instead of starting the interface.
+    pub once: bool,
+    pub format: Format,
+    /// File to log metrics to, overriding `path` in the `[log]` section of the config.
+    pub log: Option<PathBuf>,
+    /// Convey everything without colors, see [`crate::theme::Theme::plain`].
+    pub no_color: bool,
+    /// Draw with ASCII characters only.
+    pub ascii: bool,
+    /// Start with the performance overlay shown.
+    pub debug: bool,
+    /// Raster chart protocol asked for with `--graphics`.
+    #[cfg(feature = "graphics")]
+    pub graphics: Option<crate::graphics::Protocol>,
+}
+
+impl Args {
+    /// Parse the arguments of the current process. Prints the usage or version and exits
+    /// when asked to.
+    pub fn parse() -> Result<Self> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    fn parse_from(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut parsed = Self::default();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| eyre!("{flag} needs a value\n\n{USAGE}"))
+            };
+            match flag.as_str() {
+                "--profile" => parsed.profile = Some(value()?),
+                "--log" => parsed.log = Some(PathBuf::from(value()?)),
+                "--pid" => {
+                    let pid = value()?;
+                    let pid: u32 = pid
+                        .parse()
+                        .map_err(|_| eyre!("invalid PID `{pid}`\n\n{USAGE}"))?;
+                    parsed.pids.push(Pid::from_u32(pid));
+                }
+                "--exit" => parsed.exit_with_watched = true,
+                "--once" => parsed.once = true,
+                "--no-color" => parsed.no_color = true,
+                "--ascii" => parsed.ascii = true,
+                "--debug" => parsed.debug = true,
+                #[cfg(feature = "graphics")]
+                "--graphics" => {
+                    let protocol = value()?;
+                    parsed.graphics = Some(
+                        crate::graphics::Protocol::from_key(&protocol).ok_or_else(|| {
+                            eyre!("unknown graphics protocol `{protocol}`\n\n{USAGE}")
+                        })?,
+                    );
+                }
+                #[cfg(not(feature = "graphics"))]
+                "--graphics" => {
+                    return Err(eyre!("ratatop was built without the `graphics` feature"));
+                }
+                "--format" => {
+                    let format = value()?;
+                    parsed.format = Format::from_key(&format)
+                        .ok_or_else(|| eyre!("unknown format `{format}`\n\n{USAGE}"))?;
+                }
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                "-V" | "--version" => {
+                    println!("ratatop {}", env!("CARGO_PKG_VERSION"));
+                    std::process::exit(0);
+                }
+                _ => return Err(eyre!("unknown argument `{flag}`\n\n{USAGE}")),
+            }
+        }
+        Ok(parsed)
+    }
+}
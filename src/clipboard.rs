@@ -0,0 +1,11 @@
+//! Copying text to the system clipboard with the OSC 52 escape sequence, which terminals
+//! forward to the clipboard of the machine they run on, also over SSH.
+
+use std::io::{self, Write};
+
+use crate::base64;
+
+pub fn copy(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write!(out, "\x1b]52;c;{}\x07", base64::encode(text.as_bytes()))?;
+    out.flush()
+}
@@ -0,0 +1,350 @@
+use std::{cmp::Ordering, sync::OnceLock};
+
+use ratatui::layout::Constraint;
+use sysinfo::Process;
+
+use crate::{
+    energy::Energy,
+    i18n::Messages,
+    jail,
+    procfs::{self, ProcStatus},
+    template::Template,
+    units,
+};
+
+/// Narrowest width a column can be shrunk to.
+pub const MIN_WIDTH: u16 = 3;
+/// Widest width a column can be grown to.
+pub const MAX_WIDTH: u16 = 200;
+
+/// A column of the process table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Column {
+    Pid,
+    /// Parent process.
+    Ppid,
+    Name,
+    #[default]
+    Cpu,
+    /// Swapped out memory, Linux only.
+    Swap,
+    /// Thread count, Linux only.
+    Threads,
+    /// Voluntary context switches, Linux only.
+    VoluntarySwitches,
+    /// Involuntary context switches, Linux only.
+    InvoluntarySwitches,
+    /// TCP throughput, Linux only.
+    Network,
+    /// NUMA node holding most of the memory, Linux only.
+    NumaNode,
+    /// Open handles, Windows only.
+    Handles,
+    /// Power draw, macOS only.
+    Energy,
+    /// Wakeups per second, macOS only.
+    Wakeups,
+    /// Jail, FreeBSD only.
+    Jail,
+    /// Controlling terminal, `?` for daemons. Linux only.
+    Tty,
+    /// Session ID, Unix only.
+    Session,
+    /// A column from a `[column.NAME]` section of the config, indexing [`custom_columns`].
+    Custom(u8),
+}
+
+/// A column defined by the user with a format string.
+#[derive(Debug)]
+pub struct CustomColumn {
+    /// The `NAME` of the section, used like the keys of the built-in columns.
+    pub key: &'static str,
+    pub title: &'static str,
+    pub template: Template,
+}
+
+static CUSTOM_COLUMNS: OnceLock<Vec<CustomColumn>> = OnceLock::new();
+
+/// Make the custom columns of the config known. Only the first call has an effect.
+pub fn register_custom_columns(columns: Vec<CustomColumn>) {
+    let _ = CUSTOM_COLUMNS.set(columns);
+}
+
+pub fn custom_columns() -> &'static [CustomColumn] {
+    CUSTOM_COLUMNS.get().map_or(&[], Vec::as_slice)
+}
+
+/// Everything known about a process that columns can show.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessInfo<'a> {
+    pub process: &'a Process,
+    /// Details from procfs, only collected while a column needs them.
+    pub status: Option<&'a ProcStatus>,
+    /// Power draw and wakeups, macOS only.
+    pub energy: Option<&'a Energy>,
+}
+
+impl Column {
+    pub const ALL: [Column; 16] = [
+        Column::Pid,
+        Column::Ppid,
+        Column::Name,
+        Column::Cpu,
+        Column::Swap,
+        Column::Threads,
+        Column::VoluntarySwitches,
+        Column::InvoluntarySwitches,
+        Column::Network,
+        Column::NumaNode,
+        Column::Handles,
+        Column::Energy,
+        Column::Wakeups,
+        Column::Jail,
+        Column::Tty,
+        Column::Session,
+    ];
+    /// The built-in columns this platform can fill, followed by the custom ones.
+    pub fn all() -> Vec<Column> {
+        let custom = (0..custom_columns().len()).map(|index| Column::Custom(index as u8));
+        Self::ALL
+            .into_iter()
+            .filter(|column| column.is_available())
+            .chain(custom)
+            .collect()
+    }
+
+    /// Can the column show anything on this platform? Columns that cannot are neither
+    /// offered nor accepted in the config.
+    #[allow(clippy::match_like_matches_macro)] // The `cfg!` arms differ by platform.
+    fn is_available(self) -> bool {
+        match self {
+            Column::Handles => cfg!(windows),
+            Column::Energy | Column::Wakeups => cfg!(target_os = "macos"),
+            Column::Jail => cfg!(target_os = "freebsd"),
+            Column::Swap
+            | Column::Threads
+            | Column::VoluntarySwitches
+            | Column::InvoluntarySwitches
+            | Column::Network
+            | Column::NumaNode
+            | Column::Tty => cfg!(target_os = "linux"),
+            Column::Session => cfg!(unix),
+            _ => true,
+        }
+    }
+
+    /// Columns shown until the user picks others.
+    pub const DEFAULT: [Column; 3] = [Column::Pid, Column::Name, Column::Cpu];
+    /// Columns the search query is matched against.
+    pub const SEARCHED: [Column; 3] = [Column::Pid, Column::Name, Column::Cpu];
+
+    /// Identifier used in the state and config files.
+    pub fn key(self) -> &'static str {
+        match self {
+            Column::Pid => "pid",
+            Column::Ppid => "ppid",
+            Column::Name => "name",
+            Column::Cpu => "cpu",
+            Column::Swap => "swap",
+            Column::Threads => "threads",
+            Column::VoluntarySwitches => "voluntary_switches",
+            Column::InvoluntarySwitches => "involuntary_switches",
+            Column::Network => "network",
+            Column::NumaNode => "numa_node",
+            Column::Handles => "handles",
+            Column::Energy => "energy",
+            Column::Wakeups => "wakeups",
+            Column::Jail => "jail",
+            Column::Tty => "tty",
+            Column::Session => "session",
+            Column::Custom(_) => self.custom().map_or("", |custom| custom.key),
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::all().into_iter().find(|column| column.key() == key)
+    }
+
+    pub fn title(self, messages: &Messages) -> &'static str {
+        match self {
+            Column::Pid => messages.column_pid,
+            Column::Ppid => messages.column_ppid,
+            Column::Name => messages.column_name,
+            Column::Cpu => messages.column_cpu,
+            Column::Swap => messages.column_swap,
+            Column::Threads => messages.column_threads,
+            Column::VoluntarySwitches => messages.column_voluntary_switches,
+            Column::InvoluntarySwitches => messages.column_involuntary_switches,
+            Column::Network => messages.column_network,
+            Column::NumaNode => messages.column_numa_node,
+            Column::Handles => messages.column_handles,
+            Column::Energy => messages.column_energy,
+            Column::Wakeups => messages.column_wakeups,
+            Column::Jail => messages.column_jail,
+            Column::Tty => messages.column_tty,
+            Column::Session => messages.column_session,
+            Column::Custom(_) => self.custom().map_or("", |custom| custom.title),
+        }
+    }
+
+    fn custom(self) -> Option<&'static CustomColumn> {
+        match self {
+            Column::Custom(index) => custom_columns().get(index as usize),
+            _ => None,
+        }
+    }
+
+    /// Does the column show details from [`ProcStatus`]?
+    pub fn needs_status(self) -> bool {
+        self.status_field().is_some()
+            || self
+                .custom()
+                .is_some_and(|custom| custom.template.needs_status())
+    }
+
+    /// Does the column show rates from [`crate::network::NetworkMonitor`]?
+    pub fn needs_network(self) -> bool {
+        self == Column::Network
+    }
+
+    /// Does the column show the NUMA node from [`crate::numa::memory_node`]?
+    pub fn needs_numa(self) -> bool {
+        self == Column::NumaNode
+    }
+
+    /// Does the column show the terminal from [`crate::procfs::tty`]?
+    pub fn needs_tty(self) -> bool {
+        self == Column::Tty
+    }
+
+    /// The [`ProcStatus`] field shown by the column, `None` for columns backed by sysinfo.
+    fn status_field(self) -> Option<fn(&ProcStatus) -> Option<u64>> {
+        match self {
+            Column::Swap => Some(|status| status.swap),
+            Column::Threads => Some(|status| status.threads),
+            Column::VoluntarySwitches => Some(|status| status.voluntary_ctxt_switches),
+            Column::InvoluntarySwitches => Some(|status| status.nonvoluntary_ctxt_switches),
+            Column::Network => Some(|status| status.network),
+            Column::NumaNode => Some(|status| status.numa_node),
+            Column::Handles => Some(|status| status.handles),
+            Column::Jail => Some(|status| status.jail),
+            Column::Tty => Some(|status| status.tty),
+            Column::Pid
+            | Column::Ppid
+            | Column::Session
+            | Column::Energy
+            | Column::Wakeups
+            | Column::Name
+            | Column::Cpu
+            | Column::Custom(_) => None,
+        }
+    }
+
+    fn status_value(self, info: ProcessInfo) -> Option<u64> {
+        info.status.and_then(self.status_field()?)
+    }
+
+    /// Text shown in the cell of a process.
+    pub fn cell(self, info: ProcessInfo) -> String {
+        let process = info.process;
+        match self {
+            Column::Pid => process.pid().to_string(),
+            Column::Ppid => process
+                .parent()
+                .map_or_else(|| "-".to_string(), |pid| pid.to_string()),
+            Column::Name => process.name().to_string_lossy().to_string(),
+            Column::Cpu => process.cpu_usage().to_string(),
+            Column::Swap => self
+                .status_value(info)
+                .map_or_else(|| "-".to_string(), units::bytes),
+            Column::Network => self.status_value(info).map_or_else(
+                || "-".to_string(),
+                |rate| format!("{}/s", units::bytes(rate)),
+            ),
+            Column::Energy => info.energy.map_or_else(
+                || "-".to_string(),
+                |energy| format!("{} mW", energy.milliwatts),
+            ),
+            Column::Wakeups => info
+                .energy
+                .map_or_else(|| "-".to_string(), |energy| energy.wakeups.to_string()),
+            Column::Jail => self.status_value(info).map_or_else(
+                || "-".to_string(),
+                |jid| jail::name(jid).unwrap_or_else(|| jid.to_string()),
+            ),
+            Column::Tty => match self.status_value(info) {
+                Some(0) => "?".to_string(),
+                Some(device) => procfs::tty_name(device),
+                None => "-".to_string(),
+            },
+            Column::Session => process
+                .session_id()
+                .map_or_else(|| "-".to_string(), |pid| pid.to_string()),
+            Column::Threads
+            | Column::VoluntarySwitches
+            | Column::InvoluntarySwitches
+            | Column::NumaNode
+            | Column::Handles => self
+                .status_value(info)
+                .map_or_else(|| "-".to_string(), |value| value.to_string()),
+            Column::Custom(_) => self
+                .custom()
+                .map(|custom| custom.template.render(info))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Ascending order of two processes by this column.
+    pub fn compare(self, a: ProcessInfo, b: ProcessInfo) -> Ordering {
+        match self {
+            Column::Pid => a.process.pid().cmp(&b.process.pid()),
+            Column::Ppid => a.process.parent().cmp(&b.process.parent()),
+            Column::Session => a.process.session_id().cmp(&b.process.session_id()),
+            Column::Name => a
+                .process
+                .name()
+                .to_ascii_lowercase()
+                .cmp(&b.process.name().to_ascii_lowercase()),
+            Column::Cpu => a.process.cpu_usage().total_cmp(&b.process.cpu_usage()),
+            Column::Energy => {
+                let milliwatts = |info: ProcessInfo| info.energy.map(|energy| energy.milliwatts);
+                milliwatts(a).cmp(&milliwatts(b))
+            }
+            Column::Wakeups => {
+                let wakeups = |info: ProcessInfo| info.energy.map(|energy| energy.wakeups);
+                wakeups(a).cmp(&wakeups(b))
+            }
+            Column::Swap
+            | Column::Threads
+            | Column::VoluntarySwitches
+            | Column::InvoluntarySwitches
+            | Column::Network
+            | Column::NumaNode
+            | Column::Handles
+            | Column::Jail
+            | Column::Tty => self.status_value(a).cmp(&self.status_value(b)),
+            Column::Custom(_) => self
+                .custom()
+                .map_or(Ordering::Equal, |custom| custom.template.compare(a, b)),
+        }
+    }
+
+    /// Layout used until the user resizes the column.
+    pub fn default_constraint(self) -> Constraint {
+        match self {
+            Column::Pid | Column::Ppid | Column::Session => Constraint::Length(8),
+            Column::Name => Constraint::Fill(1),
+            Column::Cpu => Constraint::Length(12),
+            Column::Swap => Constraint::Length(10),
+            Column::Network => Constraint::Length(12),
+            Column::Threads | Column::NumaNode | Column::Handles | Column::Wakeups => {
+                Constraint::Length(8)
+            }
+            Column::Energy => Constraint::Length(10),
+            Column::Jail => Constraint::Length(12),
+            Column::Tty => Constraint::Length(10),
+            Column::Custom(_) => Constraint::Length(14),
+            Column::VoluntarySwitches | Column::InvoluntarySwitches => Constraint::Length(12),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+/// A command entered on the `:` command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Select the process with this PID, entered as a bare number.
+    GoTo(u32),
+    /// Terminate every process whose name contains the pattern, after confirmation.
+    KillAll(String),
+    /// Write the chart history to a CSV file, a timestamped one in the current directory
+    /// if no path is given.
+    Export(Option<PathBuf>),
+}
+
+impl Command {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (name, argument) = text
+            .split_once(char::is_whitespace)
+            .map_or((text, ""), |(name, argument)| (name, argument.trim()));
+        if let Ok(pid) = text.parse() {
+            return Ok(Command::GoTo(pid));
+        }
+        if name.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+            return Err(format!("invalid PID `{text}`"));
+        }
+        match name {
+            "killall" if !argument.is_empty() => Ok(Command::KillAll(argument.to_string())),
+            "killall" => Err("usage: killall <pattern>".to_string()),
+            "export" if argument.is_empty() => Ok(Command::Export(None)),
+            "export" => Ok(Command::Export(Some(PathBuf::from(argument)))),
+            _ => Err(format!("unknown command `{name}`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn go_to() {
+        assert_eq!(Command::parse("1234"), Ok(Command::GoTo(1234)));
+        assert_eq!(Command::parse(" 1 "), Ok(Command::GoTo(1)));
+        assert_eq!(Command::parse("4294967295"), Ok(Command::GoTo(u32::MAX)));
+    }
+
+    #[test]
+    fn invalid_pids() {
+        for text in ["-1", "4294967296", "12abc", "1.5", "12 34"] {
+            assert_eq!(
+                Command::parse(text),
+                Err(format!("invalid PID `{text}`")),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn killall() {
+        assert_eq!(
+            Command::parse("killall chrome"),
+            Ok(Command::KillAll("chrome".to_string()))
+        );
+        assert_eq!(
+            Command::parse("  killall   Web Content  "),
+            Ok(Command::KillAll("Web Content".to_string()))
+        );
+        assert_eq!(
+            Command::parse("killall"),
+            Err("usage: killall <pattern>".to_string())
+        );
+        assert_eq!(
+            Command::parse("killall   "),
+            Err("usage: killall <pattern>".to_string())
+        );
+    }
+
+    #[test]
+    fn export() {
+        assert_eq!(Command::parse("export"), Ok(Command::Export(None)));
+        assert_eq!(
+            Command::parse("export /tmp/my charts.csv"),
+            Ok(Command::Export(Some(PathBuf::from("/tmp/my charts.csv"))))
+        );
+    }
+
+    #[test]
+    fn unknown_commands() {
+        assert_eq!(
+            Command::parse("kill 12"),
+            Err("unknown command `kill`".to_string())
+        );
+        assert_eq!(
+            Command::parse("KILLALL x"),
+            Err("unknown command `KILLALL`".to_string())
+        );
+    }
+}
@@ -0,0 +1,686 @@
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+use color_eyre::{eyre::eyre, Result};
+
+use crate::{
+    columns::{self, Column, CustomColumn},
+    filter::Filter,
+    history::Retention,
+    layout::LayoutMode,
+    metrics::LogConfig,
+    plugin::{PanelConfig, PanelKind},
+    rules::RuleConfig,
+    state::State,
+    template::Template,
+};
+
+/// User configuration, read from `$XDG_CONFIG_HOME/ratatop/config`.
+///
+/// The file is a small INI dialect: `key = value` pairs, `[section]` headers and `#`
+/// comments.
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    /// Language of the user interface, e.g. `de` or `hu_HU.UTF-8`.
+    pub locale: Option<String>,
+    /// Named view presets from `[profile.NAME]` sections, selected with `--profile NAME`.
+    pub profiles: HashMap<String, Profile>,
+    /// History kept by the CPU chart, `cpu` in the `[history]` section.
+    pub cpu_history: Retention,
+    /// History kept of the memory use, `memory` in the `[history]` section.
+    pub memory_history: Retention,
+    /// History kept of the network throughput, `network` in the `[history]` section.
+    pub network_history: Retention,
+    /// History kept by the charts of `--pid` processes, `watch` in the `[history]` section.
+    pub watch_history: Retention,
+    /// History kept by the temperature chart, `sensors` in the `[history]` section.
+    pub sensor_history: Retention,
+    /// Command panels from `[panel.NAME]` sections, in the order they appear.
+    pub panels: Vec<PanelConfig>,
+    /// Commands to run on conditions, from `[rule.NAME]` sections.
+    pub rules: Vec<RuleConfig>,
+    /// Alert when a process stays in uninterruptible sleep this long, `uninterruptible` in
+    /// the `[alerts]` section.
+    pub uninterruptible_alert: Option<Duration>,
+    /// Lowercase name patterns of processes pinned to the top of the table, `names` in the
+    /// `[pins]` section.
+    pub pinned_names: Vec<String>,
+    /// Where and how often to log metrics, from the `[log]` section.
+    pub log: LogConfig,
+    /// Where to push metrics to, from the `[push]` section.
+    #[cfg(feature = "push")]
+    pub push: Option<crate::push::PushConfig>,
+}
+
+/// View settings of a named profile. Settings that are left out keep their saved value.
+#[derive(Debug, Default, Clone)]
+pub struct Profile {
+    pub columns: Option<Vec<Column>>,
+    pub sort_column: Option<Column>,
+    pub sort_descending: Option<bool>,
+    pub tree: Option<bool>,
+    pub filter: Option<String>,
+    pub layout: Option<LayoutMode>,
+}
+
+impl Profile {
+    /// Override the matching parts of `state` with the settings of this profile.
+    pub fn apply(&self, state: &mut State) {
+        if let Some(columns) = &self.columns {
+            state.columns = columns.clone();
+        }
+        if let Some(column) = self.sort_column {
+            state.sort_column = column;
+        }
+        if let Some(descending) = self.sort_descending {
+            state.sort_descending = descending;
+        }
+        if let Some(tree) = self.tree {
+            state.tree = tree;
+        }
+        if let Some(filter) = &self.filter {
+            state.filter = filter.clone();
+        }
+        if let Some(layout) = self.layout {
+            state.layout = layout;
+        }
+    }
+}
+
+impl Config {
+    /// Load the configuration file. A missing file yields the default configuration.
+    pub fn load() -> Result<Self> {
+        let Some(path) = config_dir().map(|dir| dir.join("ratatop").join("config")) else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).map_err(|err| eyre!("{}: {err}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let mut config = Self::default();
+        let entries = parse_ini(text)?;
+        // Profiles may refer to custom columns, so they have to be known first.
+        columns::register_custom_columns(custom_columns(&entries)?);
+        for entry in entries {
+            if entry.section.starts_with("column.") {
+                continue;
+            }
+            if let Some(name) = entry.section.strip_prefix("profile.") {
+                let profile = config.profiles.entry(name.to_string()).or_default();
+                match entry.key.as_str() {
+                    "columns" => {
+                        let columns = entry
+                            .value
+                            .split(',')
+                            .map(|key| Column::from_key(key.trim()))
+                            .collect::<Option<Vec<_>>>()
+                            .filter(|columns| !columns.is_empty());
+                        profile.columns = Some(columns.ok_or_else(|| entry.invalid())?);
+                    }
+                    "sort" => {
+                        profile.sort_column =
+                            Some(Column::from_key(&entry.value).ok_or_else(|| entry.invalid())?);
+                    }
+                    "descending" => profile.sort_descending = Some(entry.bool()?),
+                    "tree" => profile.tree = Some(entry.bool()?),
+                    "filter" => profile.filter = Some(entry.value),
+                    "layout" => {
+                        profile.layout = Some(
+                            LayoutMode::from_key(&entry.value).ok_or_else(|| entry.invalid())?,
+                        );
+                    }
+                    _ => return Err(entry.unknown()),
+                }
+                continue;
+            }
+            if let Some(name) = entry.section.strip_prefix("panel.") {
+                let index = match config.panels.iter().position(|panel| panel.name == name) {
+                    Some(index) => index,
+                    None => {
+                        config.panels.push(PanelConfig::new(name));
+                        config.panels.len() - 1
+                    }
+                };
+                let panel = &mut config.panels[index];
+                match entry.key.as_str() {
+                    "command" => panel.command = entry.value,
+                    "interval" => panel.interval = entry.duration()?,
+                    "type" => {
+                        panel.kind =
+                            PanelKind::from_key(&entry.value).ok_or_else(|| entry.invalid())?;
+                    }
+                    "history" => {
+                        panel.history =
+                            Retention::from_key(&entry.value).ok_or_else(|| entry.invalid())?;
+                    }
+                    _ => return Err(entry.unknown()),
+                }
+                continue;
+            }
+            if let Some(name) = entry.section.strip_prefix("rule.") {
+                let index = match config.rules.iter().position(|rule| rule.name == name) {
+                    Some(index) => index,
+                    None => {
+                        config.rules.push(RuleConfig::new(name));
+                        config.rules.len() - 1
+                    }
+                };
+                let rule = &mut config.rules[index];
+                match entry.key.as_str() {
+                    "match" => rule.condition = entry.value,
+                    "for" => rule.duration = entry.duration()?,
+                    "run" => rule.command = entry.value,
+                    "cooldown" => rule.cooldown = entry.duration()?,
+                    _ => return Err(entry.unknown()),
+                }
+                continue;
+            }
+            #[cfg(feature = "push")]
+            if entry.section == "push" {
+                use crate::push::{Protocol, Transport};
+                let push = config.push.get_or_insert_with(Default::default);
+                match entry.key.as_str() {
+                    "protocol" => {
+                        push.protocol =
+                            Protocol::from_key(&entry.value).ok_or_else(|| entry.invalid())?;
+                    }
+                    "address" => push.address = entry.value,
+                    "transport" => {
+                        push.transport =
+                            Transport::from_key(&entry.value).ok_or_else(|| entry.invalid())?;
+                    }
+                    "interval" => push.interval = entry.duration()?,
+                    "prefix" => push.prefix = entry.value,
+                    "top" => push.top = entry.value.parse().map_err(|_| entry.invalid())?,
+                    _ => return Err(entry.unknown()),
+                }
+                continue;
+            }
+            #[cfg(not(feature = "push"))]
+            if entry.section == "push" {
+                return Err(eyre!(
+                    "line {}: ratatop was built without the `push` feature",
+                    entry.line
+                ));
+            }
+            match (entry.section.as_str(), entry.key.as_str()) {
+                ("", "locale") => config.locale = Some(entry.value),
+                ("history", "cpu") => {
+                    config.cpu_history =
+                        Retention::from_key(&entry.value).ok_or_else(|| entry.invalid())?;
+                }
+                ("history", "memory") => {
+                    config.memory_history =
+                        Retention::from_key(&entry.value).ok_or_else(|| entry.invalid())?;
+                }
+                ("history", "network") => {
+                    config.network_history =
+                        Retention::from_key(&entry.value).ok_or_else(|| entry.invalid())?;
+                }
+                ("history", "watch") => {
+                    config.watch_history =
+                        Retention::from_key(&entry.value).ok_or_else(|| entry.invalid())?;
+                }
+                ("history", "sensors") => {
+                    config.sensor_history =
+                        Retention::from_key(&entry.value).ok_or_else(|| entry.invalid())?;
+                }
+                ("alerts", "uninterruptible") => {
+                    config.uninterruptible_alert = Some(entry.duration()?);
+                }
+                ("log", "path") => config.log.path = Some(PathBuf::from(entry.value)),
+                ("log", "interval") => config.log.interval = entry.duration()?,
+                ("log", "top") => {
+                    config.log.top = entry.value.parse().map_err(|_| entry.invalid())?
+                }
+                ("pins", "names") => {
+                    config.pinned_names = entry
+                        .value
+                        .split(',')
+                        .map(|name| name.trim().to_lowercase())
+                        .filter(|name| !name.is_empty())
+                        .collect();
+                }
+                _ => return Err(entry.unknown()),
+            }
+        }
+        if let Some(panel) = config.panels.iter().find(|panel| panel.command.is_empty()) {
+            return Err(eyre!("[panel.{}] needs a `command`", panel.name));
+        }
+        #[cfg(feature = "push")]
+        if config
+            .push
+            .as_ref()
+            .is_some_and(|push| push.address.is_empty())
+        {
+            return Err(eyre!("[push] needs an `address`"));
+        }
+        if let Some(rule) = config
+            .rules
+            .iter()
+            .find(|rule| rule.condition.is_empty() || rule.command.is_empty())
+        {
+            return Err(eyre!("[rule.{}] needs `match` and `run`", rule.name));
+        }
+        if let Some(rule) = config
+            .rules
+            .iter()
+            .find(|rule| Filter::parse_expression(&rule.condition).is_none())
+        {
+            return Err(eyre!(
+                "[rule.{}] `match` is not a valid expression: {}",
+                rule.name,
+                rule.condition
+            ));
+        }
+        Ok(config)
+    }
+}
+
+/// Columns from `[column.NAME]` sections with a `format` and an optional `title`.
+fn custom_columns(entries: &[Entry]) -> Result<Vec<CustomColumn>> {
+    let mut definitions: Vec<(&str, Option<Template>, Option<&str>)> = vec![];
+    for entry in entries {
+        let Some(name) = entry.section.strip_prefix("column.") else {
+            continue;
+        };
+        let index = match definitions.iter().position(|(key, ..)| *key == name) {
+            Some(index) => index,
+            None => {
+                if Column::ALL.iter().any(|column| column.key() == name) {
+                    return Err(eyre!("[column.{name}] clashes with a built-in column"));
+                }
+                definitions.push((name, None, None));
+                definitions.len() - 1
+            }
+        };
+        let definition = &mut definitions[index];
+        match entry.key.as_str() {
+            "format" => {
+                let template = Template::parse(&entry.value)
+                    .map_err(|err| eyre!("line {}: {err}", entry.line))?;
+                definition.1 = Some(template);
+            }
+            "title" => definition.2 = Some(&entry.value),
+            _ => return Err(entry.unknown()),
+        }
+    }
+    if definitions.len() > usize::from(u8::MAX) {
+        return Err(eyre!("too many custom columns"));
+    }
+    definitions
+        .into_iter()
+        .map(|(key, template, title)| {
+            let template = template.ok_or_else(|| eyre!("[column.{key}] needs a `format`"))?;
+            // Columns live for the whole run, so their names can be leaked.
+            let key: &'static str = Box::leak(key.to_string().into_boxed_str());
+            Ok(CustomColumn {
+                key,
+                title: title.map_or(key, |title| Box::leak(title.to_string().into_boxed_str())),
+                template,
+            })
+        })
+        .collect()
+}
+
+/// A single `key = value` line of an INI file.
+#[derive(Debug)]
+pub(crate) struct Entry {
+    pub line: usize,
+    pub section: String,
+    pub key: String,
+    pub value: String,
+}
+
+impl Entry {
+    pub fn invalid(&self) -> color_eyre::Report {
+        eyre!(
+            "line {}: invalid value `{}` for `{}`",
+            self.line,
+            self.value,
+            self.key
+        )
+    }
+
+    pub fn bool(&self) -> Result<bool> {
+        match self.value.as_str() {
+            "true" | "yes" | "on" => Ok(true),
+            "false" | "no" | "off" => Ok(false),
+            _ => Err(self.invalid()),
+        }
+    }
+
+    pub fn duration(&self) -> Result<Duration> {
+        parse_duration(&self.value).ok_or_else(|| self.invalid())
+    }
+
+    pub fn unknown(&self) -> color_eyre::Report {
+        if self.section.is_empty() {
+            eyre!("line {}: unknown key `{}`", self.line, self.key)
+        } else {
+            eyre!(
+                "line {}: unknown key `{}` in section [{}]",
+                self.line,
+                self.key,
+                self.section
+            )
+        }
+    }
+}
+
+/// Parse a duration like `500ms`, `90s`, `5m`, `1h` or `2d`.
+pub(crate) fn parse_duration(text: &str) -> Option<Duration> {
+    if let Some(count) = text.strip_suffix("ms") {
+        let count: u64 = count.parse().ok()?;
+        return (count > 0).then(|| Duration::from_millis(count));
+    }
+    let unit = match text.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    let count: u64 = text[..text.len() - 1].parse().ok()?;
+    (count > 0).then(|| Duration::from_secs(count * unit))
+}
+
+pub(crate) fn parse_ini(text: &str) -> Result<Vec<Entry>> {
+    let mut entries = vec![];
+    let mut section = String::new();
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(eyre!("line {line_number}: expected `key = value`"));
+        };
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        entries.push(Entry {
+            line: line_number,
+            section: section.clone(),
+            key: key.trim().to_string(),
+            value: value.to_string(),
+        });
+    }
+    Ok(entries)
+}
+
+/// `$XDG_CONFIG_HOME`, falling back to `~/.config`.
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_STATE_HOME`, falling back to `~/.local/state`.
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+fn xdg_dir(variable: &str, fallback: &str) -> Option<PathBuf> {
+    match std::env::var_os(variable) {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Custom columns are registered once per process, so every config parsed in the tests
+    /// defines the same ones, after the part under test.
+    const COLUMNS: &str = "
+[column.load]
+format = {cpu:.0}
+title = Load
+";
+
+    fn parse(text: &str) -> Result<Config> {
+        Config::parse(&format!("{text}\n{COLUMNS}"))
+    }
+
+    fn entries(text: &str) -> Vec<(String, String, String)> {
+        parse_ini(text)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.section, entry.key, entry.value))
+            .collect()
+    }
+
+    fn entry(section: &str, key: &str, value: &str) -> (String, String, String) {
+        (section.to_string(), key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn ini_sections_and_comments() {
+        let text = "\
+# a comment
+locale = de
+
+[history]
+  # an indented comment
+cpu = 1h
+[ rule.hot ]
+match = cpu > 90
+";
+        assert_eq!(
+            entries(text),
+            vec![
+                entry("", "locale", "de"),
+                entry("history", "cpu", "1h"),
+                entry("rule.hot", "match", "cpu > 90"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ini_whitespace_and_quoting() {
+        let text = "\
+  key   =   spaced value  \n\
+empty =\n\
+quoted = \"  padded  \"\n\
+inner = name == \"foo\"\n\
+half = \"open\n\
+hash = a # not a comment\n\
+equals = a = b\n";
+        assert_eq!(
+            entries(text),
+            vec![
+                entry("", "key", "spaced value"),
+                entry("", "empty", ""),
+                entry("", "quoted", "  padded  "),
+                entry("", "inner", "name == \"foo\""),
+                entry("", "half", "\"open"),
+                entry("", "hash", "a # not a comment"),
+                entry("", "equals", "a = b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ini_line_numbers() {
+        let entries = parse_ini("# comment\n\n[log]\ntop = 5\n").unwrap();
+        assert_eq!(entries[0].line, 4);
+        let err = parse_ini("[log]\nnot a pair\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: expected `key = value`");
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2s"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1m"), Some(Duration::from_secs(60)));
+        assert_eq!(parse_duration("3h"), Some(Duration::from_secs(3 * 60 * 60)));
+        assert_eq!(
+            parse_duration("2d"),
+            Some(Duration::from_secs(2 * 24 * 60 * 60))
+        );
+    }
+
+    #[test]
+    fn invalid_durations() {
+        for text in [
+            "", "5", "s", "ms", "0s", "0ms", "-1s", "1.5s", "5x", "1 s", "m5",
+        ] {
+            assert_eq!(parse_duration(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn profiles() {
+        let config = parse(
+            "\
+[profile.work]
+columns = pid, name, load
+sort = load
+descending = no
+tree = yes
+filter = cpu > 5
+layout = compact
+
+[profile.tree]
+tree = on
+",
+        )
+        .unwrap();
+        let work = &config.profiles["work"];
+        let columns: Vec<_> = work
+            .columns
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|c| c.key())
+            .collect();
+        assert_eq!(columns, ["pid", "name", "load"]);
+        assert_eq!(work.sort_column.map(Column::key), Some("load"));
+        assert_eq!(work.sort_descending, Some(false));
+        assert_eq!(work.tree, Some(true));
+        assert_eq!(work.filter.as_deref(), Some("cpu > 5"));
+        assert_eq!(work.layout, Some(LayoutMode::Compact));
+
+        let tree = &config.profiles["tree"];
+        assert_eq!(tree.tree, Some(true));
+        assert!(tree.columns.is_none() && tree.filter.is_none() && tree.layout.is_none());
+    }
+
+    #[test]
+    fn profiles_only_override_what_they_set() {
+        let config = parse("[profile.tree]\ntree = true\n").unwrap();
+        let saved = State {
+            columns: vec![Column::Pid, Column::Cpu],
+            filter: "sshd".to_string(),
+            ..State::default()
+        };
+        let mut state = saved.clone();
+        config.profiles["tree"].apply(&mut state);
+        assert!(state.tree);
+        assert_eq!(state.columns, saved.columns);
+        assert_eq!(state.sort_column, saved.sort_column);
+        assert_eq!(state.filter, "sshd");
+    }
+
+    #[test]
+    fn profile_errors() {
+        let err = |text: &str| parse(text).unwrap_err().to_string();
+        assert_eq!(
+            err("[profile.p]\ncolour = red\n"),
+            "line 2: unknown key `colour` in section [profile.p]"
+        );
+        assert_eq!(
+            err("[profile.p]\ncolumns = pid,nope\n"),
+            "line 2: invalid value `pid,nope` for `columns`"
+        );
+        assert_eq!(
+            err("[profile.p]\nsort = nope\n"),
+            "line 2: invalid value `nope` for `sort`"
+        );
+        assert_eq!(
+            err("[profile.p]\ntree = maybe\n"),
+            "line 2: invalid value `maybe` for `tree`"
+        );
+        assert_eq!(
+            err("[column.cpu]\nformat = {pid}\n"),
+            "[column.cpu] clashes with a built-in column"
+        );
+    }
+
+    #[test]
+    fn history_per_chart() {
+        let config = parse(
+            "\
+[history]
+cpu = 1h
+memory = 10m
+network = unlimited
+sensors = 90s
+",
+        )
+        .unwrap();
+        let seconds = |count| Retention::Window(Duration::from_secs(count));
+        assert_eq!(config.cpu_history, seconds(60 * 60));
+        assert_eq!(config.memory_history, seconds(600));
+        assert_eq!(config.network_history, Retention::Unlimited);
+        assert_eq!(config.sensor_history, seconds(90));
+        assert_eq!(config.watch_history, Retention::default());
+
+        let err = parse("[history]\nnetwork = forever\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2: invalid value `forever` for `network`"
+        );
+    }
+
+    #[test]
+    fn rules() {
+        let config = parse(
+            "\
+[rule.hog]
+match = mem > 4GiB && name ~ java
+for = 30s
+run = notify-send hog
+cooldown = 5m
+",
+        )
+        .unwrap();
+        let rule = &config.rules[0];
+        assert_eq!(rule.name, "hog");
+        assert_eq!(rule.condition, "mem > 4GiB && name ~ java");
+        assert_eq!(rule.duration, Duration::from_secs(30));
+        assert_eq!(rule.command, "notify-send hog");
+        assert_eq!(rule.cooldown, Duration::from_secs(300));
+    }
+
+    #[test]
+    fn rule_conditions_must_be_expressions() {
+        let err = |condition: &str| {
+            parse(&format!("[rule.r]\nmatch = {condition}\nrun = true\n"))
+                .unwrap_err()
+                .to_string()
+        };
+        for condition in ["firefox", "cpu >", "name == \"foo", "(cpu > 5"] {
+            assert_eq!(
+                err(condition),
+                format!("[rule.r] `match` is not a valid expression: {condition}")
+            );
+        }
+        assert_eq!(
+            parse("[rule.r]\nmatch = cpu > 5\n")
+                .unwrap_err()
+                .to_string(),
+            "[rule.r] needs `match` and `run`"
+        );
+    }
+}
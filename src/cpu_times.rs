@@ -0,0 +1,196 @@
+use crate::history::{History, Retention};
+
+/// Cumulative CPU time counters of the whole machine, in clock ticks.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CpuTimes {
+    user: u64,
+    nice: u64,
+    system: u64,
+    idle: u64,
+    iowait: u64,
+    irq: u64,
+    softirq: u64,
+    steal: u64,
+}
+
+/// Share of CPU time spent in each state between two samples, in percent.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Breakdown {
+    /// User time including niced processes.
+    pub user: f64,
+    /// Kernel time including interrupt handling.
+    pub system: f64,
+    pub iowait: f64,
+    pub steal: f64,
+}
+
+impl CpuTimes {
+    /// Read the aggregated `cpu` line of `/proc/stat`.
+    #[cfg(target_os = "linux")]
+    pub fn read() -> Option<Self> {
+        let stat = std::fs::read_to_string("/proc/stat").ok()?;
+        Self::parse(stat.lines().find(|line| line.starts_with("cpu "))?)
+    }
+
+    /// CPU time breakdown is only available from procfs.
+    #[cfg(not(target_os = "linux"))]
+    pub fn read() -> Option<Self> {
+        None
+    }
+
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line
+            .split_whitespace()
+            .skip(1)
+            .map(|field| field.parse().ok());
+        let mut next = || fields.next().flatten().unwrap_or(0);
+        Some(Self {
+            user: next(),
+            nice: next(),
+            system: next(),
+            idle: next(),
+            iowait: next(),
+            irq: next(),
+            softirq: next(),
+            steal: next(),
+        })
+    }
+
+    fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+
+    /// Breakdown of the time passed since `earlier`, `None` if no time has passed.
+    pub fn since(&self, earlier: &Self) -> Option<Breakdown> {
+        let elapsed = self.total().checked_sub(earlier.total())?;
+        if elapsed == 0 {
+            return None;
+        }
+        let percent =
+            |now: u64, then: u64| now.saturating_sub(then) as f64 * 100.0 / elapsed as f64;
+        Some(Breakdown {
+            user: percent(self.user + self.nice, earlier.user + earlier.nice),
+            system: percent(
+                self.system + self.irq + self.softirq,
+                earlier.system + earlier.irq + earlier.softirq,
+            ),
+            iowait: percent(self.iowait, earlier.iowait),
+            steal: percent(self.steal, earlier.steal),
+        })
+    }
+}
+
+/// Chart series of the CPU time breakdown, stacked on top of each other: each series holds
+/// the sum of its own share and the shares of the series before it.
+#[derive(Debug, Default, Clone)]
+pub struct BreakdownHistory {
+    /// User, system, iowait and steal, in this order.
+    pub series: [History; 4],
+}
+
+impl BreakdownHistory {
+    pub fn new(retention: Retention) -> Self {
+        Self {
+            series: std::array::from_fn(|_| History::new(retention)),
+        }
+    }
+
+    pub fn push(&mut self, x: f64, breakdown: Breakdown) {
+        let mut stacked = 0.0;
+        for (series, share) in self.series.iter_mut().zip([
+            breakdown.user,
+            breakdown.system,
+            breakdown.iowait,
+            breakdown.steal,
+        ]) {
+            stacked += share;
+            series.push(x, stacked);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str = "cpu  10132153 290696 3084719 46828483 16683 0 25195 0 175628 0";
+
+    #[test]
+    fn parse_proc_stat() {
+        let times = CpuTimes::parse(LINE).unwrap();
+        assert_eq!(times.user, 10132153);
+        assert_eq!(times.nice, 290696);
+        assert_eq!(times.system, 3084719);
+        assert_eq!(times.idle, 46828483);
+        assert_eq!(times.iowait, 16683);
+        assert_eq!(times.irq, 0);
+        assert_eq!(times.softirq, 25195);
+        assert_eq!(times.steal, 0);
+    }
+
+    #[test]
+    fn parse_old_kernels() {
+        // Kernels before 2.6.11 have no steal column.
+        let times = CpuTimes::parse("cpu 1 2 3 4 5 6 7").unwrap();
+        assert_eq!((times.softirq, times.steal), (7, 0));
+    }
+
+    #[test]
+    fn breakdown() {
+        let earlier = CpuTimes {
+            user: 100,
+            nice: 10,
+            system: 50,
+            idle: 800,
+            iowait: 20,
+            irq: 5,
+            softirq: 5,
+            steal: 10,
+        };
+        let now = CpuTimes {
+            user: 130,
+            nice: 20,
+            system: 60,
+            idle: 835,
+            iowait: 25,
+            irq: 5,
+            softirq: 10,
+            steal: 15,
+        };
+        let breakdown = now.since(&earlier).unwrap();
+        assert_eq!(breakdown.user, 40.0);
+        assert_eq!(breakdown.system, 15.0);
+        assert_eq!(breakdown.iowait, 5.0);
+        assert_eq!(breakdown.steal, 5.0);
+    }
+
+    #[test]
+    fn no_time_passed() {
+        let times = CpuTimes::parse(LINE).unwrap();
+        assert!(times.since(&times).is_none());
+    }
+
+    #[test]
+    fn counters_going_back() {
+        let earlier = CpuTimes::parse(LINE).unwrap();
+        // After a counter wrap or a reset, e.g. when a VM is restored, the total shrinks.
+        assert!(CpuTimes::default().since(&earlier).is_none());
+        // The iowait counter of Linux may go back while the total grows.
+        let now = CpuTimes {
+            idle: earlier.idle + 100,
+            iowait: earlier.iowait - 50,
+            ..earlier
+        };
+        let breakdown = now.since(&earlier).unwrap();
+        assert_eq!(breakdown.iowait, 0.0);
+        assert_eq!(breakdown.user, 0.0);
+    }
+}
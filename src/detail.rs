@@ -0,0 +1,460 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Cell, Clear, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+use sysinfo::{Pid, System};
+
+use crate::{
+    affinity,
+    i18n::Messages,
+    ioprio::{self, IoPriority},
+    layout,
+    procfs::{self, MemoryRegion},
+    theme::Theme,
+    units,
+};
+
+/// Details of a single process, shown in a popup over the process table.
+#[derive(Debug)]
+pub struct DetailView {
+    pub pid: Pid,
+    /// CPU affinity and I/O priority, read when the view opens and at process refreshes
+    /// rather than every frame.
+    affinity: String,
+    io_priority: String,
+    affinity_editor: Option<AffinityEditor>,
+    io_priority_editor: Option<IoPriority>,
+    maps: Option<MapsView>,
+    #[cfg(all(feature = "syscalls", target_os = "linux"))]
+    syscalls: Option<crate::syscalls::SyscallView>,
+    /// Outcome of the last action, e.g. a permission error.
+    message: Option<String>,
+}
+
+/// Core selection dialog for the CPU affinity.
+#[derive(Debug)]
+struct AffinityEditor {
+    cpus: Vec<bool>,
+    cursor: usize,
+}
+
+/// Memory mappings of the process, read once when the view is opened.
+#[derive(Debug)]
+struct MapsView {
+    /// Largest resident regions first.
+    regions: Vec<MemoryRegion>,
+    state: TableState,
+}
+
+impl DetailView {
+    pub fn new(pid: Pid, system: &System) -> Self {
+        let mut view = Self {
+            pid,
+            affinity: String::new(),
+            io_priority: String::new(),
+            affinity_editor: None,
+            io_priority_editor: None,
+            maps: None,
+            #[cfg(all(feature = "syscalls", target_os = "linux"))]
+            syscalls: None,
+            message: None,
+        };
+        view.refresh(system);
+        view
+    }
+
+    /// Read the settings of the process again.
+    pub fn refresh(&mut self, system: &System) {
+        self.affinity = affinity::get(self.pid, system.cpus().len())
+            .map(|cpus| affinity::format(&cpus))
+            .unwrap_or_else(|err| err.to_string());
+        self.io_priority = ioprio::get(self.pid)
+            .map(|priority| priority.to_string())
+            .unwrap_or_else(|err| err.to_string());
+    }
+
+    /// Handle a key press. Returns `false` when the view should be closed.
+    pub fn on_key(&mut self, key: KeyEvent, system: &System) -> bool {
+        if let Some(editor) = &mut self.affinity_editor {
+            match key.code {
+                KeyCode::Left | KeyCode::Char('h') => {
+                    editor.cursor = editor.cursor.saturating_sub(1)
+                }
+                KeyCode::Right | KeyCode::Char('l') => {
+                    editor.cursor = (editor.cursor + 1).min(editor.cpus.len().saturating_sub(1));
+                }
+                KeyCode::Char(' ') => {
+                    if let Some(allowed) = editor.cpus.get_mut(editor.cursor) {
+                        *allowed = !*allowed;
+                    }
+                }
+                KeyCode::Enter => {
+                    let result = if editor.cpus.iter().any(|&allowed| allowed) {
+                        affinity::set(self.pid, &editor.cpus)
+                    } else {
+                        Err(std::io::ErrorKind::InvalidInput.into())
+                    };
+                    self.message = result.err().map(|err| err.to_string());
+                    self.affinity_editor = None;
+                    self.refresh(system);
+                }
+                KeyCode::Esc => self.affinity_editor = None,
+                _ => {}
+            }
+            return true;
+        }
+        if let Some(priority) = &mut self.io_priority_editor {
+            let class = ioprio::Class::ALL
+                .iter()
+                .position(|&class| class == priority.class)
+                .unwrap_or(0);
+            let classes = ioprio::Class::ALL.len();
+            match key.code {
+                KeyCode::Left | KeyCode::Char('h') => {
+                    priority.class = ioprio::Class::ALL[(class + classes - 1) % classes];
+                }
+                KeyCode::Right | KeyCode::Char('l') => {
+                    priority.class = ioprio::Class::ALL[(class + 1) % classes];
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    priority.level = priority.level.saturating_sub(1)
+                }
+                KeyCode::Down | KeyCode::Char('j') => priority.level = (priority.level + 1).min(7),
+                KeyCode::Enter => {
+                    self.message = ioprio::set(self.pid, *priority)
+                        .err()
+                        .map(|err| err.to_string());
+                    self.io_priority_editor = None;
+                    self.refresh(system);
+                }
+                KeyCode::Esc => self.io_priority_editor = None,
+                _ => {}
+            }
+            return true;
+        }
+        if let Some(maps) = &mut self.maps {
+            match key.code {
+                KeyCode::Down | KeyCode::Char('j') => maps.state.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => maps.state.select_previous(),
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('m') => self.maps = None,
+                _ => {}
+            }
+            return true;
+        }
+        #[cfg(all(feature = "syscalls", target_os = "linux"))]
+        if self.syscalls.is_some() {
+            if matches!(
+                key.code,
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('s')
+            ) {
+                // Dropping the view detaches from the process.
+                self.syscalls = None;
+            }
+            return true;
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => return false,
+            KeyCode::Char('a') if cfg!(target_os = "linux") => {
+                match affinity::get(self.pid, system.cpus().len()) {
+                    Ok(cpus) => {
+                        self.message = None;
+                        self.affinity_editor = Some(AffinityEditor { cpus, cursor: 0 });
+                    }
+                    Err(err) => self.message = Some(err.to_string()),
+                }
+            }
+            KeyCode::Char('i') if cfg!(target_os = "linux") => match ioprio::get(self.pid) {
+                Ok(priority) => {
+                    self.message = None;
+                    self.io_priority_editor = Some(priority);
+                }
+                Err(err) => self.message = Some(err.to_string()),
+            },
+            KeyCode::Char('m') if cfg!(target_os = "linux") => {
+                match procfs::memory_maps(self.pid) {
+                    Ok(mut regions) => {
+                        regions.sort_by_key(|region| {
+                            std::cmp::Reverse(region.rss.unwrap_or_else(|| region.size()))
+                        });
+                        self.message = None;
+                        self.maps = Some(MapsView {
+                            regions,
+                            state: TableState::default().with_selected(0),
+                        });
+                    }
+                    Err(err) => self.message = Some(err.to_string()),
+                }
+            }
+            #[cfg(windows)]
+            KeyCode::Char(c @ ('+' | '-')) => {
+                let result = crate::windows::priority_class(self.pid).and_then(|class| {
+                    crate::windows::set_priority_class(self.pid, class.step(c == '+'))
+                });
+                self.message = result.err().map(|err| err.to_string());
+            }
+            #[cfg(all(feature = "syscalls", target_os = "linux"))]
+            KeyCode::Char('s') => match crate::syscalls::SyscallView::open(self.pid) {
+                Ok(view) => {
+                    self.message = None;
+                    self.syscalls = Some(view);
+                }
+                Err(err) => self.message = Some(err.to_string()),
+            },
+            _ => {}
+        }
+        true
+    }
+
+    pub fn render(
+        &mut self,
+        frame: &mut Frame<'_>,
+        system: &System,
+        messages: &Messages,
+        theme: &Theme,
+    ) {
+        let area = layout::centered(frame.area(), frame.area().width * 4 / 5, 16);
+        frame.render_widget(Clear, area);
+        // Only the keys that work on this platform.
+        let mut keys = vec![];
+        #[cfg(all(feature = "syscalls", target_os = "linux"))]
+        keys.push(messages.syscalls_key);
+        #[cfg(windows)]
+        keys.push(messages.priority_class_key);
+        if cfg!(target_os = "linux") {
+            keys.extend([
+                messages.affinity_key,
+                messages.io_priority_key,
+                messages.maps_key,
+            ]);
+        }
+        keys.push(messages.detail_hint);
+        let hint = keys.join(" | ");
+        let block = theme
+            .block()
+            .title(format!("{} {}", messages.detail_title, self.pid))
+            .title_bottom(hint);
+        let Some(process) = system.process(self.pid) else {
+            frame.render_widget(Paragraph::new(messages.process_gone).block(block), area);
+            return;
+        };
+
+        let cmd = process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ");
+        #[cfg_attr(not(windows), allow(unused_mut))]
+        let mut fields = vec![
+            (
+                messages.column_name,
+                process.name().to_string_lossy().to_string(),
+            ),
+            (
+                messages.label_parent,
+                process
+                    .parent()
+                    .map_or_else(|| "-".to_string(), |pid| pid.to_string()),
+            ),
+            (messages.label_status, process.status().to_string()),
+            (
+                messages.label_user,
+                process
+                    .user_id()
+                    .map_or_else(|| "-".to_string(), |uid| uid.to_string()),
+            ),
+            (messages.label_command, cmd),
+            (
+                messages.label_executable,
+                process
+                    .exe()
+                    .map_or_else(|| "-".to_string(), |exe| exe.display().to_string()),
+            ),
+            (messages.column_cpu, format!("{:.1}%", process.cpu_usage())),
+            (messages.column_memory, units::bytes(process.memory())),
+            (
+                messages.label_virtual_memory,
+                units::bytes(process.virtual_memory()),
+            ),
+            (messages.label_run_time, format_duration(process.run_time())),
+            (messages.label_affinity, self.affinity.clone()),
+            (messages.label_io_priority, self.io_priority.clone()),
+        ];
+        #[cfg(windows)]
+        fields.push((
+            messages.label_priority_class,
+            crate::windows::priority_class(self.pid)
+                .map(|class| class.to_string())
+                .unwrap_or_else(|err| err.to_string()),
+        ));
+        let mut lines: Vec<Line> = fields
+            .into_iter()
+            .map(|(label, value)| {
+                Line::from(vec![Span::from(format!("{label}: ")).bold(), value.into()])
+            })
+            .collect();
+        if let Some(message) = &self.message {
+            lines.push(Line::from(message.as_str()).fg(theme.alert));
+        }
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+
+        if let Some(editor) = &self.affinity_editor {
+            editor.render(frame, area, messages, theme);
+        }
+        if let Some(priority) = self.io_priority_editor {
+            render_io_priority_editor(priority, frame, area, messages, theme);
+        }
+        if let Some(maps) = &mut self.maps {
+            maps.render(frame, self.pid, messages, theme);
+        }
+        #[cfg(all(feature = "syscalls", target_os = "linux"))]
+        if let Some(syscalls) = &mut self.syscalls {
+            syscalls.render(frame, messages, theme);
+        }
+    }
+}
+
+impl MapsView {
+    fn render(&mut self, frame: &mut Frame<'_>, pid: Pid, messages: &Messages, theme: &Theme) {
+        let area = layout::centered(
+            frame.area(),
+            frame.area().width * 9 / 10,
+            frame.area().height * 4 / 5,
+        );
+        frame.render_widget(Clear, area);
+        let rss: u64 = self.regions.iter().filter_map(|region| region.rss).sum();
+        let header = Row::new([
+            messages.label_region,
+            messages.label_permissions,
+            messages.label_size,
+            messages.label_rss,
+            messages.label_path,
+        ])
+        .bold();
+        let rows = self.regions.iter().map(|region| {
+            Row::new([
+                Cell::from(format!("{:x}-{:x}", region.start, region.end)),
+                Cell::from(region.permissions.as_str()),
+                Cell::from(Line::from(units::bytes(region.size())).right_aligned()),
+                Cell::from(
+                    Line::from(region.rss.map_or_else(|| "-".to_string(), units::bytes))
+                        .right_aligned(),
+                ),
+                Cell::from(region.path.as_str()),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(25),
+                Constraint::Length(6),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(theme.selection_style())
+        .highlight_symbol(">>")
+        .block(
+            theme
+                .block()
+                .title(format!(
+                    "{} {pid} ({}, {} {})",
+                    messages.maps_title,
+                    self.regions.len(),
+                    messages.label_rss,
+                    units::bytes(rss)
+                ))
+                .title_bottom(messages.maps_hint),
+        );
+        frame.render_stateful_widget(table, area, &mut self.state);
+    }
+}
+
+impl AffinityEditor {
+    fn render(&self, frame: &mut Frame<'_>, over: Rect, messages: &Messages, theme: &Theme) {
+        let spans: Vec<Span> = self
+            .cpus
+            .iter()
+            .enumerate()
+            .map(|(cpu, &allowed)| {
+                let span = Span::from(format!("[{}] {cpu} ", if allowed { 'x' } else { ' ' }));
+                if cpu == self.cursor {
+                    span.style(theme.selection_style())
+                } else {
+                    span
+                }
+            })
+            .collect();
+        let area = layout::centered(over, over.width.saturating_sub(4), 6);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(Line::from(spans))
+                .wrap(Wrap { trim: false })
+                .block(
+                    theme
+                        .block()
+                        .title(messages.affinity_title)
+                        .title_bottom(messages.affinity_hint),
+                ),
+            area,
+        );
+    }
+}
+
+/// The class and level being picked, with the level greyed out for classes without levels.
+fn render_io_priority_editor(
+    priority: IoPriority,
+    frame: &mut Frame<'_>,
+    over: Rect,
+    messages: &Messages,
+    theme: &Theme,
+) {
+    let level = Span::from(format!("{}", priority.level));
+    let line = Line::from(vec![
+        Span::from(format!("< {} >", priority.class)).style(theme.selection_style()),
+        Span::from("  "),
+        if priority.class.has_level() {
+            level
+        } else {
+            level.dim()
+        },
+    ]);
+    let area = layout::centered(over, over.width.saturating_sub(4), 3);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(line).block(
+            theme
+                .block()
+                .title(messages.label_io_priority)
+                .title_bottom(messages.io_priority_hint),
+        ),
+        area,
+    );
+}
+
+/// Format seconds as `[days d ]hh:mm:ss`.
+pub fn format_duration(seconds: u64) -> String {
+    let (days, rest) = (seconds / 86400, seconds % 86400);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    );
+    if days > 0 {
+        format!("{days}d {time}")
+    } else {
+        time
+    }
+}
@@ -0,0 +1,136 @@
+//! Power draw, wakeups and App Nap of processes on macOS, from `proc_pid_rusage`, since CPU
+//! usage alone does not explain battery drain there.
+//!
+//! App Nap is not reported directly. A napped app runs at the background QoS tiers, so app
+//! bundles that spent most of their recent CPU time there are taken as napped.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
+
+use sysinfo::{Pid, System};
+
+/// Share of CPU time at background QoS above which an app counts as napped.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const NAPPED_SHARE: f64 = 0.9;
+
+/// Cumulative counters of one process.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+struct Counters {
+    /// Nanojoules.
+    energy: u64,
+    wakeups: u64,
+    /// CPU time at the maintenance and background QoS tiers.
+    background_time: u64,
+    /// CPU time at all QoS tiers.
+    qos_time: u64,
+}
+
+/// Rates of a process between two samples.
+#[derive(Debug, Clone, Copy)]
+pub struct Energy {
+    pub milliwatts: u64,
+    /// Wakeups from idle and interrupt wakeups per second.
+    pub wakeups: u64,
+}
+
+/// Samples the usage counters and turns them into rates per process.
+#[derive(Debug, Default)]
+pub struct EnergyMonitor {
+    counters: HashMap<Pid, Counters>,
+    last_sample: Option<Instant>,
+    napped: HashSet<Pid>,
+}
+
+impl EnergyMonitor {
+    /// Rates of every process since the last call. On the first call, and on other
+    /// platforms than macOS, there are none.
+    pub fn sample(&mut self, system: &System) -> HashMap<Pid, Energy> {
+        let now = Instant::now();
+        let counters: HashMap<Pid, Counters> = system
+            .processes()
+            .keys()
+            .filter_map(|&pid| counters(pid).map(|counters| (pid, counters)))
+            .collect();
+        let mut rates = HashMap::new();
+        self.napped.clear();
+        if let Some(last_sample) = self.last_sample {
+            let seconds = now.duration_since(last_sample).as_secs_f64().max(0.001);
+            for (&pid, current) in &counters {
+                let Some(previous) = self.counters.get(&pid) else {
+                    continue;
+                };
+                let energy = current.energy.saturating_sub(previous.energy);
+                let wakeups = current.wakeups.saturating_sub(previous.wakeups);
+                rates.insert(
+                    pid,
+                    Energy {
+                        milliwatts: (energy as f64 / 1e6 / seconds) as u64,
+                        wakeups: (wakeups as f64 / seconds) as u64,
+                    },
+                );
+                let qos_time = current.qos_time.saturating_sub(previous.qos_time);
+                let background_time = current
+                    .background_time
+                    .saturating_sub(previous.background_time);
+                if qos_time > 0
+                    && background_time as f64 >= qos_time as f64 * NAPPED_SHARE
+                    && is_app(system, pid)
+                {
+                    self.napped.insert(pid);
+                }
+            }
+        }
+        self.counters = counters;
+        self.last_sample = Some(now);
+        rates
+    }
+
+    /// Did the process look napped at the last sample?
+    pub fn is_napped(&self, pid: Pid) -> bool {
+        self.napped.contains(&pid)
+    }
+}
+
+/// Only app bundles are napped; daemons run at background QoS by design.
+fn is_app(system: &System, pid: Pid) -> bool {
+    system
+        .process(pid)
+        .and_then(|process| process.exe())
+        .is_some_and(|exe| exe.to_string_lossy().contains(".app/Contents/MacOS/"))
+}
+
+#[cfg(target_os = "macos")]
+fn counters(pid: Pid) -> Option<Counters> {
+    // SAFETY: the buffer is a rusage_info_v4, the struct RUSAGE_INFO_V4 asks for.
+    let mut info: libc::rusage_info_v4 = unsafe { std::mem::zeroed() };
+    let result = unsafe {
+        libc::proc_pid_rusage(
+            pid.as_u32() as libc::c_int,
+            libc::RUSAGE_INFO_V4,
+            (&mut info as *mut libc::rusage_info_v4).cast(),
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+    let background_time = info.ri_cpu_time_qos_maintenance + info.ri_cpu_time_qos_background;
+    Some(Counters {
+        energy: info.ri_billed_energy,
+        wakeups: info.ri_pkg_idle_wkups + info.ri_interrupt_wkups,
+        background_time,
+        qos_time: background_time
+            + info.ri_cpu_time_qos_default
+            + info.ri_cpu_time_qos_utility
+            + info.ri_cpu_time_qos_legacy
+            + info.ri_cpu_time_qos_user_initiated
+            + info.ri_cpu_time_qos_user_interactive,
+    })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn counters(_pid: Pid) -> Option<Counters> {
+    None
+}
@@ -0,0 +1,38 @@
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::Result;
+
+use crate::history::History;
+
+/// File name used when `:export` is given no path, e.g. `ratatop-1760000000.csv`.
+pub fn default_path() -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    PathBuf::from(format!("ratatop-{now}.csv"))
+}
+
+/// Write the named series as CSV with one `timestamp,elapsed,series,value` line per sample.
+///
+/// `elapsed` is the x coordinate of the samples, seconds since ratatop started; `now` is the
+/// current value of that clock, used to turn it into Unix timestamps.
+pub fn write_csv(path: &Path, series: &[(String, &History)], now: f64) -> Result<()> {
+    let wall_clock = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    let mut text = String::from("timestamp,elapsed,series,value\n");
+    for (name, history) in series {
+        for &(x, y) in history.points() {
+            let timestamp = wall_clock - (now - x);
+            writeln!(text, "{timestamp:.3},{x:.3},{name},{y:.3}")?;
+        }
+    }
+    std::fs::write(path, text)?;
+    Ok(())
+}
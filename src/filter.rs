@@ -0,0 +1,563 @@
+use sysinfo::Users;
+
+use crate::{
+    columns::{Column, ProcessInfo},
+    jail,
+};
+
+/// A parsed search query.
+///
+/// Queries like `cpu > 50 && name ~ "postgres"` are evaluated as predicates over the process
+/// fields; anything that does not parse as such an expression is a plain substring search.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Substring(String),
+    Expression(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Field, Op, Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Pid,
+    Ppid,
+    Name,
+    Cmd,
+    Exe,
+    User,
+    Status,
+    /// CPU usage in percent.
+    Cpu,
+    /// Resident memory in MiB.
+    Memory,
+    /// Virtual memory in MiB.
+    Virtual,
+    /// Name of the FreeBSD jail, `0` for the host.
+    Jail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Case-insensitive substring match.
+    Contains,
+    NotContains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+}
+
+/// What a field evaluates to for a process.
+enum Actual {
+    Number(f64),
+    Text(String),
+}
+
+impl Filter {
+    pub fn parse(query: &str) -> Self {
+        let query = query.trim();
+        match tokenize(query).and_then(|tokens| Parser { tokens, pos: 0 }.parse()) {
+            Some(expr) => Filter::Expression(expr),
+            None => Filter::Substring(query.to_lowercase()),
+        }
+    }
+
+    /// Parse a query that has to be an expression, like the conditions of rules, where a
+    /// substring search would be a typo silently matching the wrong processes.
+    pub fn parse_expression(query: &str) -> Option<Self> {
+        tokenize(query.trim())
+            .and_then(|tokens| Parser { tokens, pos: 0 }.parse())
+            .map(Filter::Expression)
+    }
+
+    pub fn matches(&self, info: ProcessInfo, users: &Users) -> bool {
+        match self {
+            Filter::Substring(query) => matches_substring(info, query),
+            Filter::Expression(expr) => expr.eval(info, users),
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, info: ProcessInfo, users: &Users) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(info, users) && b.eval(info, users),
+            Expr::Or(a, b) => a.eval(info, users) || b.eval(info, users),
+            Expr::Not(expr) => !expr.eval(info, users),
+            Expr::Compare(field, op, value) => compare(field.value(info, users), *op, value),
+        }
+    }
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "pid" => Field::Pid,
+            "ppid" => Field::Ppid,
+            "name" => Field::Name,
+            "cmd" => Field::Cmd,
+            "exe" => Field::Exe,
+            "user" => Field::User,
+            "status" => Field::Status,
+            "cpu" => Field::Cpu,
+            "mem" | "memory" => Field::Memory,
+            "virt" | "virtual" => Field::Virtual,
+            "jail" if cfg!(target_os = "freebsd") => Field::Jail,
+            _ => return None,
+        })
+    }
+
+    fn value(self, info: ProcessInfo, users: &Users) -> Actual {
+        let process = info.process;
+        let mib = |bytes: u64| Actual::Number(bytes as f64 / (1024.0 * 1024.0));
+        match self {
+            Field::Pid => Actual::Number(process.pid().as_u32() as f64),
+            Field::Ppid => Actual::Number(process.parent().map_or(0.0, |pid| pid.as_u32() as f64)),
+            Field::Name => Actual::Text(process.name().to_string_lossy().to_string()),
+            Field::Cmd => Actual::Text(command_line(info)),
+            Field::Exe => Actual::Text(
+                process
+                    .exe()
+                    .map(|exe| exe.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+            Field::User => Actual::Text(
+                process
+                    .user_id()
+                    .map(|uid| {
+                        users
+                            .get_user_by_id(uid)
+                            .map_or_else(|| uid.to_string(), |user| user.name().to_string())
+                    })
+                    .unwrap_or_default(),
+            ),
+            Field::Status => Actual::Text(process.status().to_string()),
+            Field::Cpu => Actual::Number(process.cpu_usage() as f64),
+            Field::Memory => mib(process.memory()),
+            Field::Virtual => mib(process.virtual_memory()),
+            Field::Jail => Actual::Text(jail::name_of(process.pid()).unwrap_or_default()),
+        }
+    }
+}
+
+fn compare(actual: Actual, op: Op, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Actual::Number(actual), Value::Number(expected)) => match op {
+            Op::Eq => actual == *expected,
+            Op::Ne => actual != *expected,
+            Op::Lt => actual < *expected,
+            Op::Le => actual <= *expected,
+            Op::Gt => actual > *expected,
+            Op::Ge => actual >= *expected,
+            Op::Contains | Op::NotContains => {
+                let contains = actual.to_string().contains(&expected.to_string());
+                contains == (op == Op::Contains)
+            }
+        },
+        (actual, expected) => {
+            let actual = match actual {
+                Actual::Number(number) => number.to_string(),
+                Actual::Text(text) => text.to_lowercase(),
+            };
+            let expected = match expected {
+                Value::Number(number) => number.to_string(),
+                Value::Text(text) => text.to_lowercase(),
+            };
+            match op {
+                Op::Eq => actual == expected,
+                Op::Ne => actual != expected,
+                Op::Lt => actual < expected,
+                Op::Le => actual <= expected,
+                Op::Gt => actual > expected,
+                Op::Ge => actual >= expected,
+                Op::Contains => actual.contains(&expected),
+                Op::NotContains => !actual.contains(&expected),
+            }
+        }
+    }
+}
+
+/// Does the lowercase `query` occur in one of the cells, the command line or the executable
+/// path of the process?
+fn matches_substring(info: ProcessInfo, query: &str) -> bool {
+    if query.is_empty()
+        || Column::SEARCHED
+            .iter()
+            .any(|column| column.cell(info).to_lowercase().contains(query))
+    {
+        return true;
+    }
+    if command_line(info).to_lowercase().contains(query) {
+        return true;
+    }
+    info.process
+        .exe()
+        .is_some_and(|exe| exe.to_string_lossy().to_lowercase().contains(query))
+}
+
+fn command_line(info: ProcessInfo) -> String {
+    info.process
+        .cmd()
+        .iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Option<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => {
+                chars.next();
+                Token::Open
+            }
+            ')' => {
+                chars.next();
+                Token::Close
+            }
+            '&' | '|' | '=' => {
+                chars.next();
+                // These only exist doubled.
+                chars.next_if_eq(&c)?;
+                match c {
+                    '&' => Token::And,
+                    '|' => Token::Or,
+                    _ => Token::Op(Op::Eq),
+                }
+            }
+            '~' => {
+                chars.next();
+                Token::Op(Op::Contains)
+            }
+            '!' => {
+                chars.next();
+                if chars.next_if_eq(&'=').is_some() {
+                    Token::Op(Op::Ne)
+                } else if chars.next_if_eq(&'~').is_some() {
+                    Token::Op(Op::NotContains)
+                } else {
+                    Token::Not
+                }
+            }
+            '<' | '>' => {
+                chars.next();
+                let equal = chars.next_if_eq(&'=').is_some();
+                Token::Op(match (c, equal) {
+                    ('<', false) => Op::Lt,
+                    ('<', true) => Op::Le,
+                    ('>', false) => Op::Gt,
+                    _ => Op::Ge,
+                })
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                // An unterminated quote makes the query a plain search.
+                loop {
+                    match chars.next()? {
+                        next if next == c => break,
+                        next => text.push(next),
+                    }
+                }
+                Token::Quoted(text)
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(next) =
+                    chars.next_if(|&next| !next.is_whitespace() && !"()&|=!<>~\"'".contains(next))
+                {
+                    word.push(next);
+                }
+                if word.is_empty() {
+                    return None;
+                }
+                Token::Word(word)
+            }
+        };
+        tokens.push(token);
+    }
+    Some(tokens)
+}
+
+/// Recursive descent parser: `or := and ("||" and)*`, `and := unary ("&&" unary)*`,
+/// `unary := "!" unary | "(" or ")" | field op value`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn parse(mut self) -> Option<Expr> {
+        let expr = self.or()?;
+        (self.pos == self.tokens.len()).then_some(expr)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Option<Expr> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Some(expr)
+    }
+
+    fn and(&mut self) -> Option<Expr> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Some(expr)
+    }
+
+    fn unary(&mut self) -> Option<Expr> {
+        match self.next()? {
+            Token::Not => Some(Expr::Not(Box::new(self.unary()?))),
+            Token::Open => {
+                let expr = self.or()?;
+                self.eat(&Token::Close).then_some(expr)
+            }
+            Token::Word(name) => {
+                let field = Field::from_name(&name.to_lowercase())?;
+                let Token::Op(op) = self.next()? else {
+                    return None;
+                };
+                let value = match self.next()? {
+                    Token::Word(word) => match word.parse() {
+                        Ok(number) => Value::Number(number),
+                        Err(_) => match (field, mib(&word)) {
+                            (Field::Memory | Field::Virtual, Some(mib)) => Value::Number(mib),
+                            _ => Value::Text(word),
+                        },
+                    },
+                    Token::Quoted(text) => Value::Text(text),
+                    _ => return None,
+                };
+                Some(Expr::Compare(field, op, value))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A size with a unit like `4GiB`, `512M` or `100kB` in MiB, the unit memory fields are
+/// compared in. Units are binary whether or not they are spelled with an `i`.
+fn mib(word: &str) -> Option<f64> {
+    let split = word
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter(|&split| split > 0)?;
+    let (number, unit) = word.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let unit = unit.to_lowercase();
+    let unit = unit
+        .strip_suffix("ib")
+        .or_else(|| unit.strip_suffix('b'))
+        .unwrap_or(&unit);
+    let factor = match unit {
+        "k" => 1.0 / 1024.0,
+        "m" => 1.0,
+        "g" => 1024.0,
+        "t" => 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(number * factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare(field: Field, op: Op, value: Value) -> Expr {
+        Expr::Compare(field, op, value)
+    }
+
+    fn cpu_above(percent: f64) -> Expr {
+        compare(Field::Cpu, Op::Gt, Value::Number(percent))
+    }
+
+    fn name_is(name: &str) -> Expr {
+        compare(Field::Name, Op::Eq, Value::Text(name.to_string()))
+    }
+
+    fn and(a: Expr, b: Expr) -> Expr {
+        Expr::And(Box::new(a), Box::new(b))
+    }
+
+    fn or(a: Expr, b: Expr) -> Expr {
+        Expr::Or(Box::new(a), Box::new(b))
+    }
+
+    fn not(expr: Expr) -> Expr {
+        Expr::Not(Box::new(expr))
+    }
+
+    fn expression(query: &str) -> Expr {
+        match Filter::parse(query) {
+            Filter::Expression(expr) => expr,
+            Filter::Substring(_) => panic!("`{query}` is not an expression"),
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            expression("cpu > 1 || cpu > 2 && name == a"),
+            or(cpu_above(1.0), and(cpu_above(2.0), name_is("a")))
+        );
+        assert_eq!(
+            expression("cpu > 1 && cpu > 2 || name == a"),
+            or(and(cpu_above(1.0), cpu_above(2.0)), name_is("a"))
+        );
+    }
+
+    #[test]
+    fn not_binds_tightest() {
+        assert_eq!(
+            expression("!cpu > 1 && name == a"),
+            and(not(cpu_above(1.0)), name_is("a"))
+        );
+        assert_eq!(
+            expression("!(cpu > 1 && name == a)"),
+            not(and(cpu_above(1.0), name_is("a")))
+        );
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(
+            expression("(cpu > 1 || cpu > 2) && name == a"),
+            and(or(cpu_above(1.0), cpu_above(2.0)), name_is("a"))
+        );
+    }
+
+    #[test]
+    fn quoted_values() {
+        assert_eq!(
+            expression(r#"name ~ "web server" && user != 'root'"#),
+            and(
+                compare(
+                    Field::Name,
+                    Op::Contains,
+                    Value::Text("web server".to_string())
+                ),
+                compare(Field::User, Op::Ne, Value::Text("root".to_string())),
+            )
+        );
+    }
+
+    #[test]
+    fn memory_units() {
+        assert_eq!(
+            expression("mem > 1.5G"),
+            compare(Field::Memory, Op::Gt, Value::Number(1536.0))
+        );
+        assert_eq!(
+            expression("virt < 512M"),
+            compare(Field::Virtual, Op::Lt, Value::Number(512.0))
+        );
+        assert_eq!(
+            expression("mem >= 4GiB"),
+            compare(Field::Memory, Op::Ge, Value::Number(4096.0))
+        );
+        assert_eq!(
+            expression("mem <= 256kB"),
+            compare(Field::Memory, Op::Le, Value::Number(0.25))
+        );
+        assert_eq!(
+            expression("mem == 100"),
+            compare(Field::Memory, Op::Eq, Value::Number(100.0))
+        );
+    }
+
+    #[test]
+    fn units_only_apply_to_memory() {
+        assert_eq!(
+            expression("name == 4G"),
+            compare(Field::Name, Op::Eq, Value::Text("4G".to_string()))
+        );
+        assert_eq!(
+            expression("mem > 4X"),
+            compare(Field::Memory, Op::Gt, Value::Text("4X".to_string()))
+        );
+    }
+
+    #[test]
+    fn malformed_queries_are_substring_searches() {
+        for query in [
+            "firefox",
+            "Web Content",
+            "cpu >",
+            "cpu > 1 &&",
+            "(cpu > 1",
+            "cpu > 1)",
+            "cpu & 1",
+            "size > 1",
+            "name = foo",
+            r#"name == "foo"#,
+        ] {
+            assert_eq!(
+                Filter::parse(query),
+                Filter::Substring(query.to_lowercase()),
+                "{query}"
+            );
+            assert_eq!(Filter::parse_expression(query), None, "{query}");
+        }
+    }
+
+    #[test]
+    fn size_units() {
+        assert_eq!(mib("4GiB"), Some(4096.0));
+        assert_eq!(mib("4gb"), Some(4096.0));
+        assert_eq!(mib("512M"), Some(512.0));
+        assert_eq!(mib("100kB"), Some(100.0 / 1024.0));
+        assert_eq!(mib("1T"), Some(1024.0 * 1024.0));
+        for word in ["4", "G", "4x", "4GiBs", "1.2.3G"] {
+            assert_eq!(mib(word), None, "{word}");
+        }
+    }
+}
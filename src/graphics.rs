@@ -0,0 +1,313 @@
+//! Raster charts for terminals that support the Kitty graphics protocol or Sixel, built with
+//! the `graphics` feature.
+//!
+//! Charts are drawn into a pixel [`Canvas`] while rendering and written to the terminal after
+//! ratatui has flushed the frame, over the cells ratatui left empty for them.
+
+use std::{
+    cell::RefCell,
+    fmt::Write as _,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use ratatui::{layout::Rect, style::Color};
+
+use crate::base64;
+
+/// How often charts are sent to the terminal, as images are much bigger than text.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum payload size of a Kitty graphics escape sequence.
+const KITTY_CHUNK: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Kitty,
+    Sixel,
+}
+
+impl Protocol {
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "kitty" => Some(Protocol::Kitty),
+            "sixel" => Some(Protocol::Sixel),
+            _ => None,
+        }
+    }
+
+    /// Guess the protocol from the environment. Only Kitty announces itself reliably.
+    pub fn detect() -> Option<Self> {
+        let kitty = std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || std::env::var("TERM").is_ok_and(|term| term.contains("kitty"));
+        kitty.then_some(Protocol::Kitty)
+    }
+}
+
+/// A chart queued during rendering.
+#[derive(Debug)]
+struct Image {
+    area: Rect,
+    canvas: Canvas,
+}
+
+#[derive(Debug)]
+pub struct Graphics {
+    protocol: Protocol,
+    /// Size of a terminal cell in pixels.
+    cell: (u16, u16),
+    queued: RefCell<Vec<Image>>,
+    /// Areas of the images on screen.
+    shown: Vec<Rect>,
+    last_flush: Option<Instant>,
+}
+
+impl Graphics {
+    /// Set up raster charts, `None` if the terminal does not report its size in pixels.
+    pub fn new(protocol: Protocol) -> Option<Self> {
+        let size = crossterm::terminal::window_size().ok()?;
+        if size.width == 0 || size.height == 0 || size.columns == 0 || size.rows == 0 {
+            return None;
+        }
+        Some(Self {
+            protocol,
+            cell: (size.width / size.columns, size.height / size.rows),
+            queued: RefCell::default(),
+            shown: vec![],
+            last_flush: None,
+        })
+    }
+
+    /// Queue a line chart of `series` (points and color) for `area`.
+    pub fn chart(
+        &self,
+        area: Rect,
+        series: &[(&[(f64, f64)], Color)],
+        x_bounds: [f64; 2],
+        y_bounds: [f64; 2],
+    ) {
+        let mut canvas = Canvas::new(area.width * self.cell.0, area.height * self.cell.1);
+        for &(points, color) in series {
+            canvas.plot(points, x_bounds, y_bounds, rgb(color));
+        }
+        self.queued.borrow_mut().push(Image { area, canvas });
+    }
+
+    /// Write the queued charts to the terminal. Returns `true` when the chart areas changed
+    /// and the screen needs a full redraw to get rid of old images.
+    pub fn flush(&mut self, out: &mut impl Write) -> io::Result<bool> {
+        let images = std::mem::take(self.queued.get_mut());
+        let areas: Vec<Rect> = images.iter().map(|image| image.area).collect();
+        if areas != self.shown {
+            if self.protocol == Protocol::Kitty {
+                // Delete all images placed by ratatop.
+                out.write_all(b"\x1b_Ga=d,d=A,q=2\x1b\\")?;
+            }
+            self.shown = areas;
+            self.last_flush = None;
+            out.flush()?;
+            return Ok(true);
+        }
+        if self
+            .last_flush
+            .is_some_and(|last_flush| last_flush.elapsed() < REFRESH_INTERVAL)
+        {
+            return Ok(false);
+        }
+        self.last_flush = Some(Instant::now());
+        for (id, image) in images.iter().enumerate() {
+            // Save the cursor, move to the top left cell of the area, draw and restore.
+            write!(out, "\x1b7\x1b[{};{}H", image.area.y + 1, image.area.x + 1)?;
+            match self.protocol {
+                Protocol::Kitty => kitty(out, &image.canvas, id + 1, image.area)?,
+                Protocol::Sixel => out.write_all(sixel(&image.canvas).as_bytes())?,
+            }
+            out.write_all(b"\x1b8")?;
+        }
+        out.flush()?;
+        Ok(false)
+    }
+}
+
+/// A bitmap whose pixels are indices into a palette, 0 being transparent.
+#[derive(Debug)]
+pub struct Canvas {
+    width: u16,
+    height: u16,
+    pixels: Vec<u8>,
+    palette: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+    fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize],
+            palette: vec![[0, 0, 0]],
+        }
+    }
+
+    fn plot(
+        &mut self,
+        points: &[(f64, f64)],
+        x_bounds: [f64; 2],
+        y_bounds: [f64; 2],
+        color: [u8; 3],
+    ) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let index = match self.palette.iter().position(|&entry| entry == color) {
+            Some(index) => index as u8,
+            None => {
+                self.palette.push(color);
+                (self.palette.len() - 1) as u8
+            }
+        };
+        let scale = |value: f64, [low, high]: [f64; 2], size: u16| {
+            let ratio = if high > low {
+                (value - low) / (high - low)
+            } else {
+                1.0
+            };
+            ratio.clamp(0.0, 1.0) * f64::from(size - 1)
+        };
+        let pixel = |&(x, y): &(f64, f64)| {
+            (
+                scale(x, x_bounds, self.width) as i32,
+                self.height as i32 - 1 - scale(y, y_bounds, self.height) as i32,
+            )
+        };
+        let pixels: Vec<_> = points.iter().map(pixel).collect();
+        for pair in pixels.windows(2) {
+            self.line(pair[0], pair[1], index);
+        }
+        if let [only] = pixels[..] {
+            self.line(only, only, index);
+        }
+    }
+
+    /// Bresenham's line algorithm, two pixels thick so it stays visible on dense displays.
+    fn line(&mut self, (mut x0, mut y0): (i32, i32), (x1, y1): (i32, i32), index: u8) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        let mut error = dx + dy;
+        loop {
+            for (x, y) in [(x0, y0), (x0, y0 - 1)] {
+                if (0..self.width as i32).contains(&x) && (0..self.height as i32).contains(&y) {
+                    self.pixels[y as usize * self.width as usize + x as usize] = index;
+                }
+            }
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x0 += sx;
+            }
+            if doubled <= dx {
+                error += dx;
+                y0 += sy;
+            }
+        }
+    }
+}
+
+/// Transmit and place the canvas as RGBA data, replacing the previous image with the same id.
+/// The image is placed below the text, so popups stay readable.
+fn kitty(out: &mut impl Write, canvas: &Canvas, id: usize, area: Rect) -> io::Result<()> {
+    let mut rgba = Vec::with_capacity(canvas.pixels.len() * 4);
+    for &index in &canvas.pixels {
+        let [r, g, b] = canvas.palette[index as usize];
+        rgba.extend([r, g, b, if index == 0 { 0 } else { 255 }]);
+    }
+    let payload = base64::encode(&rgba);
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK).collect();
+    for (n, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(n + 1 < chunks.len());
+        if n == 0 {
+            write!(
+                out,
+                "\x1b_Ga=T,f=32,s={},v={},i={id},c={},r={},z=-1,C=1,q=2,m={more};",
+                canvas.width, canvas.height, area.width, area.height
+            )?;
+        } else {
+            write!(out, "\x1b_Gm={more};")?;
+        }
+        out.write_all(chunk)?;
+        out.write_all(b"\x1b\\")?;
+    }
+    Ok(())
+}
+
+/// Encode the canvas as a Sixel image with a transparent background.
+fn sixel(canvas: &Canvas) -> String {
+    let mut text = format!("\x1bP0;1;0q\"1;1;{};{}", canvas.width, canvas.height);
+    for (index, [r, g, b]) in canvas.palette.iter().enumerate().skip(1) {
+        let percent = |value: &u8| u32::from(*value) * 100 / 255;
+        let _ = write!(
+            text,
+            "#{index};2;{};{};{}",
+            percent(r),
+            percent(g),
+            percent(b)
+        );
+    }
+    let width = canvas.width as usize;
+    for band in (0..canvas.height as usize).step_by(6) {
+        for index in 1..canvas.palette.len() as u8 {
+            let sixels: Vec<u8> = (0..width)
+                .map(|x| {
+                    (0..6)
+                        .filter(|row| {
+                            canvas
+                                .pixels
+                                .get((band + row) * width + x)
+                                .is_some_and(|&pixel| pixel == index)
+                        })
+                        .fold(0, |bits, row| bits | 1 << row)
+                })
+                .collect();
+            if sixels.iter().all(|&bits| bits == 0) {
+                continue;
+            }
+            let _ = write!(text, "#{index}");
+            let mut x = 0;
+            while x < width {
+                let run = sixels[x..]
+                    .iter()
+                    .take_while(|&&bits| bits == sixels[x])
+                    .count();
+                let c = char::from(63 + sixels[x]);
+                if run > 3 {
+                    let _ = write!(text, "!{run}{c}");
+                } else {
+                    text.extend(std::iter::repeat_n(c, run));
+                }
+                x += run;
+            }
+            text.push('$');
+        }
+        text.push('-');
+    }
+    text.push_str("\x1b\\");
+    text
+}
+
+/// Approximate RGB values of terminal colors.
+fn rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Rgb(r, g, b) => [r, g, b],
+        Color::Black => [0, 0, 0],
+        Color::Red | Color::LightRed => [220, 50, 47],
+        Color::Green | Color::LightGreen => [38, 162, 105],
+        Color::Yellow | Color::LightYellow => [200, 160, 0],
+        Color::Blue | Color::LightBlue => [38, 139, 210],
+        Color::Magenta | Color::LightMagenta => [192, 56, 203],
+        Color::Cyan | Color::LightCyan => [42, 161, 179],
+        Color::Gray | Color::DarkGray => [128, 128, 128],
+        _ => [200, 200, 200],
+    }
+}
@@ -0,0 +1,49 @@
+//! Spotting processes whose resident memory keeps growing, an at-a-glance hint at leaks.
+
+use std::collections::{HashMap, VecDeque};
+
+use sysinfo::{Pid, System};
+
+/// Number of process refreshes the trend is judged over.
+pub const WINDOW: usize = 30;
+
+/// Recent resident memory of every process.
+#[derive(Debug, Default)]
+pub struct MemoryGrowth {
+    samples: HashMap<Pid, VecDeque<u64>>,
+}
+
+impl MemoryGrowth {
+    /// Record the memory of all processes, after a refresh.
+    pub fn sample(&mut self, system: &System) {
+        self.samples.retain(|pid, _| system.process(*pid).is_some());
+        for (&pid, process) in system.processes() {
+            let samples = self.samples.entry(pid).or_default();
+            samples.push_back(process.memory());
+            if samples.len() > WINDOW {
+                samples.pop_front();
+            }
+        }
+    }
+
+    /// Has the memory of the process never shrunk over the whole window, and grown in at
+    /// least a quarter of the refreshes? Single allocations then do not count as a trend.
+    pub fn is_growing(&self, pid: Pid) -> bool {
+        let Some(samples) = self.samples.get(&pid) else {
+            return false;
+        };
+        if samples.len() < WINDOW {
+            return false;
+        }
+        let mut increases = 0;
+        for (before, after) in samples.iter().zip(samples.iter().skip(1)) {
+            if after < before {
+                return false;
+            }
+            if after > before {
+                increases += 1;
+            }
+        }
+        increases >= WINDOW / 4
+    }
+}
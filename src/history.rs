@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use crate::config::parse_duration;
+
+/// Number of samples an unlimited history keeps before halving its resolution.
+pub const MAX_POINTS: usize = 4096;
+
+/// How much of a metric's history a chart keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Retention {
+    /// Keep the samples of the last window, dropping older ones.
+    Window(Duration),
+    /// Keep the whole session, averaging neighbouring samples whenever
+    /// [`MAX_POINTS`] is reached, so long sessions still show the overall trend.
+    #[default]
+    Unlimited,
+}
+
+impl Retention {
+    /// Parse `unlimited` or a duration like `90s`, `5m`, `1h` or `2d`.
+    pub fn from_key(key: &str) -> Option<Self> {
+        if key == "unlimited" {
+            return Some(Retention::Unlimited);
+        }
+        parse_duration(key).map(Retention::Window)
+    }
+}
+
+/// A metric sampled over time, with running statistics over the whole session.
+///
+/// The x coordinate of a sample is the number of seconds since ratatop started.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    retention: Retention,
+    points: Vec<(f64, f64)>,
+    sum: f64,
+    count: u64,
+    peak: f64,
+}
+
+impl History {
+    pub fn new(retention: Retention) -> Self {
+        Self {
+            retention,
+            ..Self::default()
+        }
+    }
+
+    pub fn push(&mut self, x: f64, y: f64) {
+        self.points.push((x, y));
+        self.sum += y;
+        self.count += 1;
+        self.peak = self.peak.max(y);
+        match self.retention {
+            Retention::Window(window) => {
+                let start = x - window.as_secs_f64();
+                let expired = self.points.partition_point(|&(x, _)| x < start);
+                self.points.drain(..expired);
+            }
+            Retention::Unlimited if self.points.len() > MAX_POINTS => {
+                self.points = self
+                    .points
+                    .chunks(2)
+                    .map(|pair| {
+                        let n = pair.len() as f64;
+                        let x = pair.iter().map(|&(x, _)| x).sum::<f64>() / n;
+                        let y = pair.iter().map(|&(_, y)| y).sum::<f64>() / n;
+                        (x, y)
+                    })
+                    .collect();
+            }
+            Retention::Unlimited => {}
+        }
+    }
+
+    /// Samples as chart coordinates.
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// First and last x coordinate, for the chart bounds.
+    pub fn x_bounds(&self) -> [f64; 2] {
+        match (self.points.first(), self.points.last()) {
+            (Some(&(first, _)), Some(&(last, _))) => [first, last],
+            _ => [0.0, 0.0],
+        }
+    }
+
+    pub fn current(&self) -> Option<f64> {
+        self.points.last().map(|&(_, y)| y)
+    }
+
+    /// Average of all samples taken this session.
+    pub fn average(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    /// Highest sample taken this session.
+    pub fn peak(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.peak)
+    }
+}
+
+/// The time span the charts show: the whole history while following the latest samples, or
+/// a fixed window panned back in time while new samples keep coming in.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Viewport {
+    /// Right edge and width of the window in chart coordinates, `None` while following.
+    window: Option<(f64, f64)>,
+}
+
+impl Viewport {
+    /// Seconds between the right edge of the window and `latest`, `None` while following.
+    pub fn behind(&self, latest: f64) -> Option<f64> {
+        self.window.map(|(end, _)| latest - end)
+    }
+
+    /// Move the window by a quarter of its width, back in time for a negative `direction`.
+    /// `bounds` is the extent of the history; the first pan narrows the window to half of it.
+    /// Panning past the latest sample follows it again.
+    pub fn pan(&mut self, direction: f64, bounds: [f64; 2]) {
+        let span = bounds[1] - bounds[0];
+        if span <= 0.0 {
+            return;
+        }
+        let (end, width) = self.window.unwrap_or((bounds[1], span / 2.0));
+        let end = (end + direction * width / 4.0).max(bounds[0] + width.min(span));
+        self.window = (end < bounds[1]).then_some((end, width));
+    }
+
+    pub fn follow(&mut self) {
+        self.window = None;
+    }
+
+    /// The visible part of a chart with the given `bounds`.
+    pub fn apply(&self, bounds: [f64; 2]) -> [f64; 2] {
+        match self.window {
+            Some((end, width)) => [end - width, end],
+            None => bounds,
+        }
+    }
+}
+
+/// The points within `bounds`, plus one on either side so lines reach the edges.
+pub fn visible(points: &[(f64, f64)], bounds: [f64; 2]) -> &[(f64, f64)] {
+    let start = points
+        .partition_point(|&(x, _)| x < bounds[0])
+        .saturating_sub(1);
+    let end = (points.partition_point(|&(x, _)| x <= bounds[1]) + 1).min(points.len());
+    &points[start.min(end)..end]
+}
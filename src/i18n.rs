@@ -0,0 +1,608 @@
+/// All user-visible strings of the interface.
+#[derive(Debug, Clone, Copy)]
+pub struct Messages {
+    pub cpu: &'static str,
+    pub cpu_total: &'static str,
+    pub cpu_user: &'static str,
+    pub cpu_system: &'static str,
+    pub cpu_iowait: &'static str,
+    pub cpu_steal: &'static str,
+    pub processes: &'static str,
+    pub services: &'static str,
+    pub search: &'static str,
+    pub column_pid: &'static str,
+    pub column_name: &'static str,
+    pub column_cpu: &'static str,
+    pub column_memory: &'static str,
+    pub column_unit: &'static str,
+    pub column_processes: &'static str,
+    pub column_ppid: &'static str,
+    pub column_swap: &'static str,
+    pub column_threads: &'static str,
+    pub column_voluntary_switches: &'static str,
+    pub column_involuntary_switches: &'static str,
+    pub column_network: &'static str,
+    pub column_handles: &'static str,
+    pub column_energy: &'static str,
+    pub column_wakeups: &'static str,
+    pub column_jail: &'static str,
+    pub column_tty: &'static str,
+    pub column_session: &'static str,
+    pub column_numa_node: &'static str,
+    pub detail_title: &'static str,
+    pub maps_key: &'static str,
+    pub affinity_key: &'static str,
+    pub io_priority_key: &'static str,
+    pub detail_hint: &'static str,
+    pub process_gone: &'static str,
+    pub exited: &'static str,
+    pub label_parent: &'static str,
+    pub label_status: &'static str,
+    pub label_user: &'static str,
+    pub label_command: &'static str,
+    pub label_executable: &'static str,
+    pub label_virtual_memory: &'static str,
+    pub label_priority_class: &'static str,
+    pub label_run_time: &'static str,
+    pub label_affinity: &'static str,
+    pub label_io_priority: &'static str,
+    pub io_priority_hint: &'static str,
+    pub affinity_title: &'static str,
+    pub affinity_hint: &'static str,
+    pub maps_title: &'static str,
+    pub maps_hint: &'static str,
+    pub label_region: &'static str,
+    pub label_permissions: &'static str,
+    pub label_size: &'static str,
+    pub label_rss: &'static str,
+    pub label_path: &'static str,
+    pub syscalls_title: &'static str,
+    pub syscalls_hint: &'static str,
+    pub priority_class_key: &'static str,
+    pub syscalls_key: &'static str,
+    pub label_calls: &'static str,
+    pub label_per_second: &'static str,
+    pub label_recent: &'static str,
+    pub logins: &'static str,
+    pub label_tty: &'static str,
+    pub label_from: &'static str,
+    pub label_login: &'static str,
+    pub temperatures: &'static str,
+    pub no_sensors: &'static str,
+    pub sensor_warn: &'static str,
+    pub sensor_critical: &'static str,
+    pub power: &'static str,
+    pub cores: &'static str,
+    pub max_frequency: &'static str,
+    pub numa_node: &'static str,
+    pub no_parent: &'static str,
+    pub no_children: &'static str,
+    pub no_zombies: &'static str,
+    pub uninterruptible: &'static str,
+    pub kill_title: &'static str,
+    pub kill_hint: &'static str,
+    pub no_such_pid: &'static str,
+    pub pid_hidden: &'static str,
+    pub no_match: &'static str,
+    pub killed: &'static str,
+    pub failed: &'static str,
+    pub chart_now: &'static str,
+    pub chart_average: &'static str,
+    pub chart_peak: &'static str,
+    pub exported: &'static str,
+    pub diff_title: &'static str,
+    pub copied: &'static str,
+    pub snapshot_taken: &'static str,
+    pub no_snapshot: &'static str,
+    pub rule_triggered: &'static str,
+    pub summary_total: &'static str,
+    pub summary_shown: &'static str,
+    pub status_running: &'static str,
+    pub status_sleeping: &'static str,
+    pub status_zombie: &'static str,
+    pub help_hint: &'static str,
+    pub perf_title: &'static str,
+    pub perf_frame: &'static str,
+    pub perf_draw: &'static str,
+    pub perf_refresh: &'static str,
+    pub perf_rows: &'static str,
+    pub perf_last: &'static str,
+    pub perf_average: &'static str,
+    pub perf_max: &'static str,
+    pub help_title: &'static str,
+    /// Key bindings and what they do.
+    pub help: &'static [(&'static str, &'static str)],
+}
+
+const EN: Messages = Messages {
+    cpu: "CPU",
+    cpu_total: "Total",
+    cpu_user: "User",
+    cpu_system: "System",
+    cpu_iowait: "IO wait",
+    cpu_steal: "Steal",
+    processes: "Processes",
+    services: "Services",
+    search: "Search",
+    column_pid: "PID",
+    column_name: "Name",
+    column_cpu: "CPU",
+    column_memory: "Memory",
+    column_unit: "Unit",
+    column_processes: "Processes",
+    column_ppid: "PPID",
+    column_swap: "Swap",
+    column_threads: "Threads",
+    column_voluntary_switches: "Vol. CS",
+    column_involuntary_switches: "Invol. CS",
+    column_network: "Network",
+    column_handles: "Handles",
+    column_energy: "Power",
+    column_wakeups: "Wakeups",
+    column_jail: "Jail",
+    column_tty: "TTY",
+    column_session: "Session",
+    column_numa_node: "Node",
+    detail_title: "Process",
+    maps_key: "m memory maps",
+    affinity_key: "a affinity",
+    io_priority_key: "i I/O priority",
+    detail_hint: "Esc close",
+    process_gone: "The process has exited.",
+    exited: "exited",
+    label_parent: "Parent",
+    label_status: "Status",
+    label_user: "User",
+    label_command: "Command",
+    label_executable: "Executable",
+    label_virtual_memory: "Virtual memory",
+    label_priority_class: "Priority class",
+    label_run_time: "Run time",
+    label_affinity: "CPU affinity",
+    label_io_priority: "I/O priority",
+    io_priority_hint: "Left/Right class | Up/Down level | Enter apply | Esc cancel",
+    affinity_title: "CPU affinity",
+    affinity_hint: "Space toggle | Enter apply | Esc cancel",
+    maps_title: "Memory maps",
+    maps_hint: "j/k scroll | Esc back",
+    label_region: "Region",
+    label_permissions: "Perms",
+    label_size: "Size",
+    label_rss: "RSS",
+    label_path: "File",
+    syscalls_title: "System calls",
+    syscalls_hint: "Esc back (tracing slows the process down)",
+    priority_class_key: "+/- priority",
+    syscalls_key: "s system calls",
+    label_calls: "Calls",
+    label_per_second: "Per s",
+    label_recent: "Recent",
+    logins: "Users",
+    label_tty: "TTY",
+    label_from: "From",
+    label_login: "Login",
+    temperatures: "Temperatures",
+    no_sensors: "No temperature sensors found",
+    sensor_warn: "high",
+    sensor_critical: "critical",
+    power: "Power",
+    cores: "Cores",
+    max_frequency: "max",
+    numa_node: "Node",
+    no_parent: "The parent is not in the table",
+    no_children: "No child processes in the table",
+    no_zombies: "No zombie processes",
+    uninterruptible: "in uninterruptible sleep (D state) for",
+    kill_title: "Kill processes matching",
+    kill_hint: "y kill | n cancel",
+    no_such_pid: "No process with PID",
+    pid_hidden: "Not shown with the current filter: PID",
+    no_match: "No process matches",
+    killed: "terminated",
+    failed: "failed",
+    chart_now: "now",
+    chart_average: "avg",
+    chart_peak: "peak",
+    exported: "History written to",
+    diff_title: "Changes since the snapshot",
+    copied: "Copied to the clipboard",
+    snapshot_taken: "Snapshot taken",
+    no_snapshot: "No snapshot yet, press S",
+    rule_triggered: "Rule triggered",
+    summary_total: "total",
+    summary_shown: "shown",
+    status_running: "running",
+    status_sleeping: "sleeping",
+    status_zombie: "zombie",
+    help_hint: "? help",
+    perf_title: "Performance",
+    perf_frame: "Frame",
+    perf_draw: "Draw",
+    perf_refresh: "Refresh",
+    perf_rows: "Rows",
+    perf_last: "Last",
+    perf_average: "Average",
+    perf_max: "Max",
+    help_title: "Keys",
+    help: &[
+        ("q, Esc", "quit"),
+        ("j / k", "move selection"),
+        ("s", "search, Enter to apply, Esc to cancel"),
+        ("c", "clear the filter"),
+        ("Up / Down", "search history"),
+        ("[ / ]", "focus column"),
+        ("{ / }", "move column left / right"),
+        ("+ / -", "widen / narrow column"),
+        ("=", "reset column width"),
+        ("o", "sort by column, again to reverse"),
+        ("t", "tree view"),
+        ("v", "switch layout"),
+        ("p", "pin / unpin process"),
+        ("u", "services (systemd or Windows)"),
+        ("Enter", "process details / processes of service"),
+        ("a", "add hidden column"),
+        ("x / X", "hide column / default columns"),
+        (
+            "Left / Right, End",
+            "pan the charts back in time, follow the latest",
+        ),
+        ("S / d", "take a snapshot / compare with it"),
+        ("y", "copy process details to the clipboard"),
+        ("P / C", "jump to parent / first child"),
+        ("Z", "jump to the next zombie process"),
+        (":", "command line: <pid>, killall <pattern>, export [file]"),
+        ("D", "performance overlay"),
+        ("?", "this help"),
+    ],
+};
+
+const DE: Messages = Messages {
+    cpu: "CPU",
+    cpu_total: "Gesamt",
+    cpu_user: "Benutzer",
+    cpu_system: "System",
+    cpu_iowait: "IO-Warten",
+    cpu_steal: "Steal",
+    processes: "Prozesse",
+    services: "Dienste",
+    search: "Suche",
+    column_pid: "PID",
+    column_name: "Name",
+    column_cpu: "CPU",
+    column_memory: "Speicher",
+    column_unit: "Unit",
+    column_processes: "Prozesse",
+    column_ppid: "PPID",
+    column_swap: "Swap",
+    column_threads: "Threads",
+    column_voluntary_switches: "Freiw. KW",
+    column_involuntary_switches: "Unfreiw. KW",
+    column_network: "Netzwerk",
+    column_handles: "Handles",
+    column_energy: "Leistung",
+    column_wakeups: "Aufwachen",
+    column_jail: "Jail",
+    column_tty: "TTY",
+    column_session: "Sitzung",
+    column_numa_node: "Knoten",
+    detail_title: "Prozess",
+    maps_key: "m Speicherbereiche",
+    affinity_key: "a Affinität",
+    io_priority_key: "i E/A-Priorität",
+    detail_hint: "Esc schließen",
+    process_gone: "Der Prozess wurde beendet.",
+    exited: "wurde beendet",
+    label_parent: "Elternprozess",
+    label_status: "Status",
+    label_user: "Benutzer",
+    label_command: "Befehl",
+    label_executable: "Programmdatei",
+    label_virtual_memory: "Virtueller Speicher",
+    label_priority_class: "Prioritätsklasse",
+    label_run_time: "Laufzeit",
+    label_affinity: "CPU-Affinität",
+    label_io_priority: "E/A-Priorität",
+    io_priority_hint: "Links/Rechts Klasse | Hoch/Runter Stufe | Enter anwenden | Esc abbrechen",
+    affinity_title: "CPU-Affinität",
+    affinity_hint: "Leertaste umschalten | Enter anwenden | Esc abbrechen",
+    maps_title: "Speicherbereiche",
+    maps_hint: "j/k blättern | Esc zurück",
+    label_region: "Bereich",
+    label_permissions: "Rechte",
+    label_size: "Größe",
+    label_rss: "RSS",
+    label_path: "Datei",
+    syscalls_title: "Systemaufrufe",
+    syscalls_hint: "Esc zurück (die Ablaufverfolgung bremst den Prozess)",
+    priority_class_key: "+/- Priorität",
+    syscalls_key: "s Systemaufrufe",
+    label_calls: "Aufrufe",
+    label_per_second: "Pro s",
+    label_recent: "Zuletzt",
+    logins: "Benutzer",
+    label_tty: "TTY",
+    label_from: "Von",
+    label_login: "Anmeldung",
+    temperatures: "Temperaturen",
+    no_sensors: "Keine Temperatursensoren gefunden",
+    sensor_warn: "hoch",
+    sensor_critical: "kritisch",
+    power: "Leistung",
+    cores: "Kerne",
+    max_frequency: "max.",
+    numa_node: "Knoten",
+    no_parent: "Der Elternprozess ist nicht in der Tabelle",
+    no_children: "Keine Kindprozesse in der Tabelle",
+    no_zombies: "Keine Zombie-Prozesse",
+    uninterruptible: "in ununterbrechbarem Schlaf (D-Zustand) seit",
+    kill_title: "Prozesse beenden, passend zu",
+    kill_hint: "y beenden | n abbrechen",
+    no_such_pid: "Kein Prozess mit PID",
+    pid_hidden: "Mit dem aktuellen Filter nicht sichtbar: PID",
+    no_match: "Kein Prozess passt zu",
+    killed: "beendet",
+    failed: "fehlgeschlagen",
+    chart_now: "jetzt",
+    chart_average: "Mittel",
+    chart_peak: "max",
+    exported: "Verlauf gespeichert in",
+    diff_title: "Änderungen seit der Momentaufnahme",
+    copied: "In die Zwischenablage kopiert",
+    snapshot_taken: "Momentaufnahme erstellt",
+    no_snapshot: "Noch keine Momentaufnahme, S drücken",
+    rule_triggered: "Regel ausgelöst",
+    summary_total: "gesamt",
+    summary_shown: "angezeigt",
+    status_running: "laufend",
+    status_sleeping: "schlafend",
+    status_zombie: "Zombie",
+    help_hint: "? Hilfe",
+    perf_title: "Leistung",
+    perf_frame: "Frame",
+    perf_draw: "Zeichnen",
+    perf_refresh: "Abfrage",
+    perf_rows: "Zeilen",
+    perf_last: "Zuletzt",
+    perf_average: "Mittel",
+    perf_max: "Max",
+    help_title: "Tasten",
+    help: &[
+        ("q, Esc", "beenden"),
+        ("j / k", "Auswahl bewegen"),
+        ("s", "suchen, Enter übernehmen, Esc abbrechen"),
+        ("c", "Filter löschen"),
+        ("Hoch / Runter", "Suchverlauf"),
+        ("[ / ]", "Spalte wählen"),
+        ("{ / }", "Spalte nach links / rechts verschieben"),
+        ("+ / -", "Spalte verbreitern / verschmälern"),
+        ("=", "Spaltenbreite zurücksetzen"),
+        ("o", "nach Spalte sortieren, erneut umkehren"),
+        ("t", "Baumansicht"),
+        ("v", "Anordnung wechseln"),
+        ("p", "Prozess oben anheften / lösen"),
+        ("u", "Dienste (systemd oder Windows)"),
+        ("Enter", "Prozessdetails / Prozesse des Dienstes"),
+        ("a", "ausgeblendete Spalte zeigen"),
+        ("x / X", "Spalte ausblenden / Standardspalten"),
+        (
+            "Links / Rechts, Ende",
+            "Diagramme zeitlich verschieben, dem Neuesten folgen",
+        ),
+        ("S / d", "Momentaufnahme / damit vergleichen"),
+        ("y", "Prozessdetails in die Zwischenablage kopieren"),
+        ("P / C", "zum Eltern- / ersten Kindprozess springen"),
+        ("Z", "zum nächsten Zombie-Prozess springen"),
+        (":", "Befehlszeile: <PID>, killall <Muster>, export [Datei]"),
+        ("D", "Leistungsanzeige"),
+        ("?", "diese Hilfe"),
+    ],
+};
+
+const HU: Messages = Messages {
+    cpu: "CPU",
+    cpu_total: "Összes",
+    cpu_user: "Felhasználói",
+    cpu_system: "Rendszer",
+    cpu_iowait: "IO-várakozás",
+    cpu_steal: "Elvett",
+    processes: "Folyamatok",
+    services: "Szolgáltatások",
+    search: "Keresés",
+    column_pid: "PID",
+    column_name: "Név",
+    column_cpu: "CPU",
+    column_memory: "Memória",
+    column_unit: "Egység",
+    column_processes: "Folyamatok",
+    column_ppid: "PPID",
+    column_swap: "Swap",
+    column_threads: "Szálak",
+    column_voluntary_switches: "Önk. KV",
+    column_involuntary_switches: "Kénysz. KV",
+    column_network: "Hálózat",
+    column_handles: "Leírók",
+    column_energy: "Teljesítmény",
+    column_wakeups: "Ébredések",
+    column_jail: "Börtön",
+    column_tty: "TTY",
+    column_session: "Munkamenet",
+    column_numa_node: "Csomópont",
+    detail_title: "Folyamat",
+    maps_key: "m memóriaterületek",
+    affinity_key: "a affinitás",
+    io_priority_key: "i I/O-prioritás",
+    detail_hint: "Esc bezárás",
+    process_gone: "A folyamat kilépett.",
+    exited: "kilépett",
+    label_parent: "Szülő",
+    label_status: "Állapot",
+    label_user: "Felhasználó",
+    label_command: "Parancs",
+    label_executable: "Futtatható fájl",
+    label_virtual_memory: "Virtuális memória",
+    label_priority_class: "Prioritási osztály",
+    label_run_time: "Futási idő",
+    label_affinity: "CPU-affinitás",
+    label_io_priority: "I/O-prioritás",
+    io_priority_hint: "Bal/Jobb osztály | Fel/Le szint | Enter alkalmaz | Esc mégse",
+    affinity_title: "CPU-affinitás",
+    affinity_hint: "Szóköz váltás | Enter alkalmaz | Esc mégse",
+    maps_title: "Memóriaterületek",
+    maps_hint: "j/k görgetés | Esc vissza",
+    label_region: "Terület",
+    label_permissions: "Jogok",
+    label_size: "Méret",
+    label_rss: "RSS",
+    label_path: "Fájl",
+    syscalls_title: "Rendszerhívások",
+    syscalls_hint: "Esc vissza (a nyomkövetés lassítja a folyamatot)",
+    priority_class_key: "+/- prioritás",
+    syscalls_key: "s rendszerhívások",
+    label_calls: "Hívások",
+    label_per_second: "Mp-enként",
+    label_recent: "Legutóbbiak",
+    logins: "Felhasználók",
+    label_tty: "TTY",
+    label_from: "Honnan",
+    label_login: "Bejelentkezés",
+    temperatures: "Hőmérséklet",
+    no_sensors: "Nem található hőmérséklet-érzékelő",
+    sensor_warn: "magas",
+    sensor_critical: "kritikus",
+    power: "Teljesítmény",
+    cores: "Magok",
+    max_frequency: "max.",
+    numa_node: "Csomópont",
+    no_parent: "A szülőfolyamat nincs a táblázatban",
+    no_children: "Nincs gyermekfolyamat a táblázatban",
+    no_zombies: "Nincs zombi folyamat",
+    uninterruptible: "megszakíthatatlan alvásban (D állapot) ennyi ideje:",
+    kill_title: "Egyező folyamatok leállítása:",
+    kill_hint: "y leállítás | n mégse",
+    no_such_pid: "Nincs folyamat ezzel a PID-del:",
+    pid_hidden: "A jelenlegi szűrővel nem látható: PID",
+    no_match: "Nincs egyező folyamat:",
+    killed: "leállítva",
+    failed: "sikertelen",
+    chart_now: "most",
+    chart_average: "átl",
+    chart_peak: "csúcs",
+    exported: "Előzmények mentve ide:",
+    diff_title: "Változások a pillanatkép óta",
+    copied: "Vágólapra másolva",
+    snapshot_taken: "Pillanatkép elkészült",
+    no_snapshot: "Még nincs pillanatkép, nyomd meg az S-t",
+    rule_triggered: "Szabály aktiválva",
+    summary_total: "összesen",
+    summary_shown: "látható",
+    status_running: "futó",
+    status_sleeping: "alvó",
+    status_zombie: "zombi",
+    help_hint: "? súgó",
+    perf_title: "Teljesítmény",
+    perf_frame: "Képkocka",
+    perf_draw: "Rajzolás",
+    perf_refresh: "Frissítés",
+    perf_rows: "Sorok",
+    perf_last: "Utolsó",
+    perf_average: "Átlag",
+    perf_max: "Max",
+    help_title: "Billentyűk",
+    help: &[
+        ("q, Esc", "kilépés"),
+        ("j / k", "kijelölés mozgatása"),
+        ("s", "keresés, Enter alkalmazás, Esc mégse"),
+        ("c", "szűrő törlése"),
+        ("Fel / Le", "keresési előzmények"),
+        ("[ / ]", "oszlop kiválasztása"),
+        ("{ / }", "oszlop mozgatása balra / jobbra"),
+        ("+ / -", "oszlop szélesítése / keskenyítése"),
+        ("=", "oszlopszélesség visszaállítása"),
+        ("o", "rendezés oszlop szerint, újra: fordított"),
+        ("t", "fanézet"),
+        ("v", "elrendezés váltása"),
+        ("p", "folyamat kitűzése / feloldása"),
+        ("u", "szolgáltatások (systemd vagy Windows)"),
+        ("Enter", "folyamat részletei / a szolgáltatás folyamatai"),
+        ("a", "rejtett oszlop hozzáadása"),
+        ("x / X", "oszlop elrejtése / alapértelmezett oszlopok"),
+        (
+            "Bal / Jobb, End",
+            "grafikonok görgetése az időben, legfrissebb követése",
+        ),
+        ("S / d", "pillanatkép / összevetés vele"),
+        ("y", "folyamat adatainak másolása a vágólapra"),
+        ("P / C", "ugrás a szülőre / első gyermekre"),
+        ("Z", "ugrás a következő zombi folyamatra"),
+        (":", "parancssor: <pid>, killall <minta>, export [fájl]"),
+        ("D", "teljesítmény-kijelző"),
+        ("?", "ez a súgó"),
+    ],
+};
+
+impl Messages {
+    /// Pick the catalog for the configured locale, falling back to the usual locale
+    /// environment variables and finally to English.
+    pub fn resolve(configured: Option<&str>) -> Self {
+        let locale = configured.map(str::to_string).or_else(|| {
+            ["RATATOP_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .filter_map(|variable| std::env::var(variable).ok())
+                .find(|value| !value.is_empty())
+        });
+        locale.map_or(EN, |locale| Self::for_locale(&locale))
+    }
+
+    /// `locale` may be a bare language code or a full POSIX locale like `de_AT.UTF-8`.
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match language.as_str() {
+            "de" => DE,
+            "hu" => HU,
+            _ => EN,
+        }
+    }
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        EN
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locales() {
+        assert_eq!(
+            Messages::for_locale("de_AT.UTF-8").chart_average,
+            DE.chart_average
+        );
+        assert_eq!(Messages::for_locale("hu").chart_average, HU.chart_average);
+        assert_eq!(
+            Messages::for_locale("fr_FR").chart_average,
+            EN.chart_average
+        );
+    }
+
+    #[test]
+    fn help_keys_are_translated() {
+        for messages in [DE, HU] {
+            assert_eq!(messages.help.len(), EN.help.len());
+            for (key, _) in messages.help {
+                assert!(!key.contains("Left") && !key.contains("Right"), "{key}");
+            }
+        }
+    }
+
+    #[test]
+    fn german_average_is_spelled_out() {
+        assert_eq!(DE.chart_average, "Mittel");
+    }
+}
@@ -0,0 +1,118 @@
+//! I/O scheduling class and priority of a process, like `ionice`. Linux only.
+
+use std::{fmt, io};
+
+use sysinfo::Pid;
+
+/// `IOPRIO_WHO_PROCESS` of the ioprio syscalls.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const WHO_PROCESS: i32 = 1;
+/// The class is stored above the level in the priority value.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const CLASS_SHIFT: u32 = 13;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Class {
+    /// No class set: best effort at a level derived from the CPU nice value.
+    None,
+    /// Served first, regardless of other processes. Needs root.
+    RealTime,
+    BestEffort,
+    /// Only served when no other process needs the disk.
+    Idle,
+}
+
+impl Class {
+    /// The classes in the order the editor cycles through them.
+    pub const ALL: [Class; 4] = [Class::None, Class::RealTime, Class::BestEffort, Class::Idle];
+
+    /// Do the levels 0 (highest) to 7 (lowest) apply to this class?
+    pub fn has_level(self) -> bool {
+        matches!(self, Class::RealTime | Class::BestEffort)
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Class::None => "none",
+            Class::RealTime => "realtime",
+            Class::BestEffort => "best-effort",
+            Class::Idle => "idle",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoPriority {
+    pub class: Class,
+    /// 0 to 7, lower is served first.
+    pub level: u8,
+}
+
+impl fmt::Display for IoPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.class.has_level() {
+            write!(f, "{} {}", self.class, self.level)
+        } else {
+            write!(f, "{}", self.class)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn get(pid: Pid) -> io::Result<IoPriority> {
+    // SAFETY: ioprio_get only takes integers.
+    let value = unsafe { libc::syscall(libc::SYS_ioprio_get, WHO_PROCESS, pid.as_u32()) };
+    if value < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let class = match value >> CLASS_SHIFT {
+        1 => Class::RealTime,
+        2 => Class::BestEffort,
+        3 => Class::Idle,
+        _ => Class::None,
+    };
+    Ok(IoPriority {
+        class,
+        level: (value & 0x7) as u8,
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn set(pid: Pid, priority: IoPriority) -> io::Result<()> {
+    let class: i64 = match priority.class {
+        Class::None => 0,
+        Class::RealTime => 1,
+        Class::BestEffort => 2,
+        Class::Idle => 3,
+    };
+    let level = if priority.class.has_level() {
+        i64::from(priority.level.min(7))
+    } else {
+        0
+    };
+    // SAFETY: ioprio_set only takes integers.
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_ioprio_set,
+            WHO_PROCESS,
+            pid.as_u32(),
+            class << CLASS_SHIFT | level,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get(_pid: Pid) -> io::Result<IoPriority> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set(_pid: Pid, _priority: IoPriority) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
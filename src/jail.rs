@@ -0,0 +1,77 @@
+//! FreeBSD jails of processes, like the `JID` column of `ps -o jid` and the names `jls`
+//! lists.
+
+use sysinfo::Pid;
+
+/// ID of the jail the process runs in, 0 for the host.
+#[cfg(target_os = "freebsd")]
+pub fn jail_of(pid: Pid) -> Option<u64> {
+    let mib = [
+        libc::CTL_KERN,
+        libc::KERN_PROC,
+        libc::KERN_PROC_PID,
+        pid.as_u32() as libc::c_int,
+    ];
+    // SAFETY: sysctl writes at most `len` bytes into the kinfo_proc.
+    let mut info: libc::kinfo_proc = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::kinfo_proc>();
+    let result = unsafe {
+        libc::sysctl(
+            mib.as_ptr(),
+            mib.len() as libc::c_uint,
+            (&mut info as *mut libc::kinfo_proc).cast(),
+            &mut len,
+            std::ptr::null(),
+            0,
+        )
+    };
+    if result != 0 || len == 0 {
+        return None;
+    }
+    u64::try_from(info.ki_jid).ok()
+}
+
+/// Jails only exist on FreeBSD.
+#[cfg(not(target_os = "freebsd"))]
+pub fn jail_of(_pid: Pid) -> Option<u64> {
+    None
+}
+
+/// Name of a jail, `0` for the host.
+#[cfg(target_os = "freebsd")]
+pub fn name(jid: u64) -> Option<String> {
+    let mut jid = libc::c_int::try_from(jid).ok()?;
+    let mut name = [0u8; 256];
+    let iov = |base: *mut u8, len: usize| libc::iovec {
+        iov_base: base.cast(),
+        iov_len: len,
+    };
+    // jail_get takes parameter names and values in alternating vectors.
+    let mut params = [
+        iov(b"jid\0".as_ptr().cast_mut(), 4),
+        iov(
+            (&mut jid as *mut libc::c_int).cast(),
+            std::mem::size_of::<libc::c_int>(),
+        ),
+        iov(b"name\0".as_ptr().cast_mut(), 5),
+        iov(name.as_mut_ptr(), name.len()),
+    ];
+    // SAFETY: the vectors point to live buffers of the given lengths, and the names are
+    // only read.
+    if unsafe { libc::jail_get(params.as_mut_ptr(), params.len() as libc::c_uint, 0) } < 0 {
+        return None;
+    }
+    let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    Some(String::from_utf8_lossy(&name[..len]).to_string())
+}
+
+#[cfg(not(target_os = "freebsd"))]
+pub fn name(_jid: u64) -> Option<String> {
+    None
+}
+
+/// Name of the jail of a process, falling back to its ID.
+pub fn name_of(pid: Pid) -> Option<String> {
+    let jid = jail_of(pid)?;
+    Some(name(jid).unwrap_or_else(|| jid.to_string()))
+}
@@ -0,0 +1,87 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+
+/// Arrangement of the panes on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayoutMode {
+    /// CPU chart, panels and the process table.
+    #[default]
+    Full,
+    /// CPU chart and the process table.
+    Compact,
+    /// Only the process table.
+    Processes,
+}
+
+/// Screen areas of the panes visible in a [`LayoutMode`].
+#[derive(Debug, Clone, Copy)]
+pub struct Areas {
+    pub chart: Option<Rect>,
+    pub panels: Option<Rect>,
+    pub processes: Rect,
+}
+
+impl LayoutMode {
+    pub const ALL: [LayoutMode; 3] = [LayoutMode::Full, LayoutMode::Compact, LayoutMode::Processes];
+
+    /// Identifier used in the state and config files.
+    pub fn key(self) -> &'static str {
+        match self {
+            LayoutMode::Full => "full",
+            LayoutMode::Compact => "compact",
+            LayoutMode::Processes => "processes",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.key() == key)
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn areas(self, area: Rect) -> Areas {
+        match self {
+            LayoutMode::Full => {
+                let [chart, panels, processes] = Layout::vertical([
+                    Constraint::Percentage(25),
+                    Constraint::Fill(1),
+                    Constraint::Fill(1),
+                ])
+                .areas(area);
+                Areas {
+                    chart: Some(chart),
+                    panels: Some(panels),
+                    processes,
+                }
+            }
+            LayoutMode::Compact => {
+                let [chart, processes] =
+                    Layout::vertical([Constraint::Percentage(25), Constraint::Fill(1)]).areas(area);
+                Areas {
+                    chart: Some(chart),
+                    panels: None,
+                    processes,
+                }
+            }
+            LayoutMode::Processes => Areas {
+                chart: None,
+                panels: None,
+                processes: area,
+            },
+        }
+    }
+}
+
+/// A `width` x `height` rectangle in the middle of `area`, shrunk to fit.
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
@@ -0,0 +1,79 @@
+//! Logged-in users from the utmpx database, like `who`.
+
+/// A login session.
+#[derive(Debug, Clone)]
+pub struct Login {
+    pub user: String,
+    /// Terminal like `pts/0`.
+    pub tty: String,
+    /// Remote host for SSH and similar sessions, empty for local ones.
+    pub host: String,
+    /// Unix time of the login.
+    pub since: i64,
+}
+
+/// The current login sessions, oldest first. Empty where utmpx is not available.
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "freebsd"))]
+pub fn logins() -> Vec<Login> {
+    let text = |chars: &[libc::c_char]| {
+        let bytes: Vec<u8> = chars
+            .iter()
+            .take_while(|&&c| c != 0)
+            .map(|&c| c as u8)
+            .collect();
+        String::from_utf8_lossy(&bytes).to_string()
+    };
+    let mut logins = vec![];
+    // SAFETY: the utmpx functions are only used from the UI thread, and every entry is copied
+    // before the next call overwrites it.
+    unsafe {
+        libc::setutxent();
+        loop {
+            let entry = libc::getutxent();
+            let Some(entry) = entry.as_ref() else {
+                break;
+            };
+            if entry.ut_type != libc::USER_PROCESS {
+                continue;
+            }
+            logins.push(Login {
+                user: text(&entry.ut_user),
+                tty: text(&entry.ut_line),
+                host: text(&entry.ut_host),
+                since: i64::from(entry.ut_tv.tv_sec),
+            });
+        }
+        libc::endutxent();
+    }
+    logins.sort_by_key(|login| login.since);
+    logins
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "freebsd")))]
+pub fn logins() -> Vec<Login> {
+    vec![]
+}
+
+/// Local date and time of a Unix time, like `2024-05-01 09:30`.
+#[cfg(unix)]
+pub fn format_time(seconds: i64) -> String {
+    let time = seconds as libc::time_t;
+    // SAFETY: localtime_r only writes the given struct.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return seconds.to_string();
+    }
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min
+    )
+}
+
+#[cfg(not(unix))]
+pub fn format_time(seconds: i64) -> String {
+    seconds.to_string()
+}
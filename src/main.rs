@@ -1,11 +1,89 @@
 pub use app::App;
+use cli::Args;
+use color_eyre::eyre::eyre;
+use config::Config;
+use state::State;
+use theme::{Background, Theme};
 
+pub mod affinity;
 pub mod app;
+pub mod ascii_chart;
+pub mod base64;
+pub mod cli;
+pub mod clipboard;
+pub mod columns;
+pub mod command;
+pub mod config;
+pub mod cpu_times;
+pub mod detail;
+pub mod energy;
+pub mod export;
+pub mod filter;
+#[cfg(feature = "graphics")]
+pub mod graphics;
+pub mod growth;
+pub mod history;
+pub mod i18n;
+pub mod ioprio;
+pub mod jail;
+pub mod layout;
+pub mod logins;
+pub mod metrics;
+pub mod network;
+pub mod numa;
+pub mod perf;
+pub mod plugin;
+pub mod power;
+pub mod procfs;
+#[cfg(feature = "push")]
+pub mod push;
+pub mod report;
+pub mod rows;
+pub mod rules;
+pub mod sensors;
+pub mod snapshot;
+pub mod state;
+pub mod stuck;
+pub mod summary;
+#[cfg(all(feature = "syscalls", target_os = "linux"))]
+pub mod syscalls;
+pub mod systemd;
+pub mod template;
+pub mod theme;
+pub mod tree;
+pub mod trend;
+pub mod units;
+pub mod watch;
+#[cfg(windows)]
+pub mod windows;
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
+    let args = Args::parse()?;
+    if args.once {
+        return report::print(args.format, &args.pids);
+    }
+    let config = Config::load()?;
+    let mut state = State::load();
+    let mut saved_state = None;
+    if let Some(name) = &args.profile {
+        let profile = config
+            .profiles
+            .get(name)
+            .ok_or_else(|| eyre!("unknown profile `{name}`"))?;
+        saved_state = Some(state.clone());
+        profile.apply(&mut state);
+    }
+    // https://no-color.org: any non-empty value disables colors.
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let mut theme = if args.no_color || no_color {
+        Theme::plain()
+    } else {
+        Theme::new(Background::detect())
+    };
+    theme.ascii = args.ascii;
     let terminal = ratatui::init();
-    let result = App::new().run(terminal);
+    let result = App::new(config, args, state, saved_state, theme).run(terminal);
     ratatui::restore();
     result
 }
@@ -0,0 +1,215 @@
+//! Periodic samples of system metrics, appended to a file while the interface runs so a
+//! long session leaves analyzable data behind.
+
+use std::{
+    fmt::Write as _,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::Result;
+use sysinfo::{Process, System};
+
+use crate::report;
+
+/// Settings of the metrics log, `--log` and the `[log]` section of the config.
+#[derive(Debug, Clone)]
+pub struct LogConfig {
+    pub path: Option<PathBuf>,
+    /// Time between two samples.
+    pub interval: Duration,
+    /// How many of the busiest processes each sample lists.
+    pub top: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            interval: Duration::from_secs(10),
+            top: 5,
+        }
+    }
+}
+
+/// System metrics at one point in time.
+#[derive(Debug, Clone)]
+pub struct Sample {
+    /// Unix time in seconds.
+    pub timestamp: f64,
+    /// Percent of all CPUs.
+    pub cpu: f32,
+    pub used_memory: u64,
+    pub total_memory: u64,
+    pub used_swap: u64,
+    pub load: [f64; 3],
+    /// The processes using the most CPU, busiest first.
+    pub top: Vec<TopProcess>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TopProcess {
+    pub pid: u32,
+    pub name: String,
+    pub cpu: f32,
+    pub memory: u64,
+}
+
+impl Sample {
+    pub fn collect(system: &System, top: usize) -> Self {
+        let mut processes: Vec<&Process> = system.processes().values().collect();
+        processes.sort_by(|a, b| b.cpu_usage().total_cmp(&a.cpu_usage()));
+        let load = System::load_average();
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64(),
+            cpu: system.global_cpu_usage(),
+            used_memory: system.used_memory(),
+            total_memory: system.total_memory(),
+            used_swap: system.used_swap(),
+            load: [load.one, load.five, load.fifteen],
+            top: processes
+                .into_iter()
+                .take(top)
+                .map(|process| TopProcess {
+                    pid: process.pid().as_u32(),
+                    name: process.name().to_string_lossy().to_string(),
+                    cpu: process.cpu_usage(),
+                    memory: process.memory(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// File format of the log, chosen by the extension: JSON Lines for `.jsonl` and `.json`,
+/// CSV otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Csv,
+    JsonLines,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("jsonl" | "json") => Format::JsonLines,
+            _ => Format::Csv,
+        }
+    }
+}
+
+/// Appends a [`Sample`] to the log file every [`LogConfig::interval`].
+#[derive(Debug)]
+pub struct MetricsLog {
+    path: PathBuf,
+    format: Format,
+    interval: Duration,
+    top: usize,
+    last: Option<Instant>,
+}
+
+impl MetricsLog {
+    /// `None` unless a log file is configured.
+    pub fn new(config: &LogConfig) -> Option<Self> {
+        let path = config.path.clone()?;
+        Some(Self {
+            format: Format::from_path(&path),
+            path,
+            interval: config.interval,
+            top: config.top,
+            last: None,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a sample if the interval has passed since the last one.
+    pub fn record(&mut self, system: &System) -> Result<()> {
+        let now = Instant::now();
+        if self
+            .last
+            .is_some_and(|last| now.duration_since(last) < self.interval)
+        {
+            return Ok(());
+        }
+        self.last = Some(now);
+        let sample = Sample::collect(system, self.top);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut text = String::new();
+        match self.format {
+            Format::Csv => {
+                if file.metadata()?.len() == 0 {
+                    text.push_str(
+                        "timestamp,cpu,used_memory,total_memory,used_swap,load1,load5,load15,top\n",
+                    );
+                }
+                csv_line(&mut text, &sample)?;
+            }
+            Format::JsonLines => json_line(&mut text, &sample)?,
+        }
+        file.write_all(text.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// The busiest processes go into a single column like `"postgres (812) 43.0%; ..."`.
+fn csv_line(text: &mut String, sample: &Sample) -> std::fmt::Result {
+    let top: Vec<String> = sample
+        .top
+        .iter()
+        .map(|process| format!("{} ({}) {:.1}%", process.name, process.pid, process.cpu))
+        .collect();
+    writeln!(
+        text,
+        "{:.3},{:.2},{},{},{},{:.2},{:.2},{:.2},\"{}\"",
+        sample.timestamp,
+        sample.cpu,
+        sample.used_memory,
+        sample.total_memory,
+        sample.used_swap,
+        sample.load[0],
+        sample.load[1],
+        sample.load[2],
+        top.join("; ").replace('"', "\"\""),
+    )
+}
+
+fn json_line(text: &mut String, sample: &Sample) -> std::fmt::Result {
+    let top: Vec<String> = sample
+        .top
+        .iter()
+        .map(|process| {
+            format!(
+                "{{\"pid\": {}, \"name\": {}, \"cpu_usage\": {:.2}, \"memory\": {}}}",
+                process.pid,
+                report::string(&process.name),
+                process.cpu,
+                process.memory
+            )
+        })
+        .collect();
+    writeln!(
+        text,
+        "{{\"timestamp\": {:.3}, \"cpu_usage\": {:.2}, \"used_memory\": {}, \"total_memory\": {}, \
+         \"used_swap\": {}, \"load_average\": [{:.2}, {:.2}, {:.2}], \"top\": [{}]}}",
+        sample.timestamp,
+        sample.cpu,
+        sample.used_memory,
+        sample.total_memory,
+        sample.used_swap,
+        sample.load[0],
+        sample.load[1],
+        sample.load[2],
+        top.join(", "),
+    )
+}
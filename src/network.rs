@@ -0,0 +1,223 @@
+//! Per-process network throughput on Linux, like nethogs.
+//!
+//! The kernel keeps byte counters for every TCP socket, which the sock_diag netlink interface
+//! reports together with the socket inode. The inodes are attributed to processes through
+//! their `/proc/<pid>/fd/*` links. UDP and other sockets have no counters and are not seen.
+
+use std::{collections::HashMap, time::Instant};
+
+use sysinfo::Pid;
+
+/// Samples the socket counters and turns them into rates per process.
+#[derive(Debug, Default)]
+pub struct NetworkMonitor {
+    /// Sent and received bytes of each TCP socket at the last sample, by inode.
+    counters: HashMap<u32, u64>,
+    last_sample: Option<Instant>,
+}
+
+impl NetworkMonitor {
+    /// Bytes per second each of `pids` sent and received since the last call. Processes
+    /// without TCP sockets are missing; on the first call, all of them are.
+    pub fn sample(&mut self, pids: impl Iterator<Item = Pid>) -> HashMap<Pid, u64> {
+        let Ok(counters) = tcp_counters() else {
+            return HashMap::new();
+        };
+        let now = Instant::now();
+        let mut rates = HashMap::new();
+        if let Some(last_sample) = self.last_sample {
+            let seconds = now.duration_since(last_sample).as_secs_f64().max(0.001);
+            let mut bytes: HashMap<Pid, u64> = HashMap::new();
+            for (inode, pid) in socket_owners(pids) {
+                let Some(&total) = counters.get(&inode) else {
+                    continue;
+                };
+                // Sockets opened since the last sample count from zero.
+                let previous = self.counters.get(&inode).copied().unwrap_or(0);
+                *bytes.entry(pid).or_default() += total.saturating_sub(previous);
+            }
+            rates = bytes
+                .into_iter()
+                .map(|(pid, bytes)| (pid, (bytes as f64 / seconds) as u64))
+                .collect();
+        }
+        self.counters = counters;
+        self.last_sample = Some(now);
+        rates
+    }
+}
+
+/// The process owning each socket inode. Sockets shared after a fork go to the lowest PID.
+#[cfg(target_os = "linux")]
+fn socket_owners(pids: impl Iterator<Item = Pid>) -> HashMap<u32, Pid> {
+    let mut pids: Vec<Pid> = pids.collect();
+    pids.sort();
+    let mut owners = HashMap::new();
+    for pid in pids {
+        // Other users' descriptors are not readable without privileges.
+        let Ok(entries) = std::fs::read_dir(format!("/proc/{pid}/fd")) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(target) = std::fs::read_link(entry.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|target| target.strip_prefix("socket:["))
+                .and_then(|target| target.strip_suffix(']'))
+                .and_then(|inode| inode.parse().ok());
+            if let Some(inode) = inode {
+                owners.entry(inode).or_insert(pid);
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(not(target_os = "linux"))]
+fn socket_owners(_pids: impl Iterator<Item = Pid>) -> HashMap<u32, Pid> {
+    HashMap::new()
+}
+
+#[cfg(target_os = "linux")]
+fn tcp_counters() -> std::io::Result<HashMap<u32, u64>> {
+    use std::os::fd::{FromRawFd, OwnedFd};
+
+    // SAFETY: creating a socket has no preconditions; the descriptor is owned from here on.
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+            libc::NETLINK_SOCK_DIAG,
+        )
+    };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // SAFETY: `fd` is a valid descriptor nothing else owns.
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+    let mut counters = HashMap::new();
+    for family in [libc::AF_INET, libc::AF_INET6] {
+        sock_diag::dump(&fd, family as u8, &mut counters)?;
+    }
+    Ok(counters)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn tcp_counters() -> std::io::Result<HashMap<u32, u64>> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// Just enough of `linux/sock_diag.h` and `linux/inet_diag.h` to dump TCP sockets.
+#[cfg(target_os = "linux")]
+mod sock_diag {
+    use std::{
+        collections::HashMap,
+        io,
+        os::fd::{AsRawFd, OwnedFd},
+    };
+
+    const SOCK_DIAG_BY_FAMILY: u16 = 20;
+    /// Attribute carrying a `struct tcp_info`.
+    const INET_DIAG_INFO: u16 = 2;
+    const NLMSG_HEADER: usize = 16;
+    /// `struct inet_diag_req_v2`.
+    const REQUEST: usize = 56;
+    /// `struct inet_diag_msg`, with the inode at the end.
+    const MESSAGE: usize = 72;
+    const INODE_OFFSET: usize = 68;
+    /// Offsets of `tcpi_bytes_acked` and `tcpi_bytes_received` in `struct tcp_info`.
+    const BYTES_ACKED: usize = 120;
+    const BYTES_RECEIVED: usize = 128;
+
+    /// Add the counters of the TCP sockets of an address family to `counters`.
+    pub fn dump(fd: &OwnedFd, family: u8, counters: &mut HashMap<u32, u64>) -> io::Result<()> {
+        let mut request = Vec::with_capacity(NLMSG_HEADER + REQUEST);
+        request.extend(((NLMSG_HEADER + REQUEST) as u32).to_ne_bytes());
+        request.extend(SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+        request.extend(((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+        // Sequence number and port id.
+        request.extend([0; 8]);
+        request.extend([
+            family,
+            libc::IPPROTO_TCP as u8,
+            1 << (INET_DIAG_INFO - 1),
+            0,
+        ]);
+        // Sockets in any state.
+        request.extend(u32::MAX.to_ne_bytes());
+        // No filter on addresses and ports.
+        request.resize(NLMSG_HEADER + REQUEST, 0);
+        // SAFETY: the buffer is valid for its length.
+        let sent = unsafe { libc::send(fd.as_raw_fd(), request.as_ptr().cast(), request.len(), 0) };
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            // SAFETY: the kernel writes at most the length of the buffer.
+            let received =
+                unsafe { libc::recv(fd.as_raw_fd(), buffer.as_mut_ptr().cast(), buffer.len(), 0) };
+            if received < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let data = &buffer[..received as usize];
+            let mut offset = 0;
+            while offset + NLMSG_HEADER <= data.len() {
+                let length = read_u32(data, offset) as usize;
+                if length < NLMSG_HEADER || offset + length > data.len() {
+                    return Err(io::ErrorKind::InvalidData.into());
+                }
+                match i32::from(read_u16(data, offset + 4)) {
+                    libc::NLMSG_DONE => return Ok(()),
+                    libc::NLMSG_ERROR => {
+                        let errno = read_u32(data, offset + NLMSG_HEADER) as i32;
+                        return Err(io::Error::from_raw_os_error(-errno));
+                    }
+                    _ => socket(&data[offset + NLMSG_HEADER..offset + length], counters),
+                }
+                offset += length.next_multiple_of(4);
+            }
+        }
+    }
+
+    /// Read the inode and counters of one `inet_diag_msg` and its attributes.
+    fn socket(message: &[u8], counters: &mut HashMap<u32, u64>) {
+        if message.len() < MESSAGE {
+            return;
+        }
+        let inode = read_u32(message, INODE_OFFSET);
+        let mut offset = MESSAGE;
+        while offset + 4 <= message.len() {
+            let length = read_u16(message, offset) as usize;
+            if length < 4 || offset + length > message.len() {
+                return;
+            }
+            let info = &message[offset + 4..offset + length];
+            if read_u16(message, offset + 2) == INET_DIAG_INFO
+                && inode != 0
+                && info.len() >= BYTES_RECEIVED + 8
+            {
+                counters.insert(
+                    inode,
+                    read_u64(info, BYTES_ACKED) + read_u64(info, BYTES_RECEIVED),
+                );
+            }
+            offset += length.next_multiple_of(4);
+        }
+    }
+
+    fn read_u16(data: &[u8], offset: usize) -> u16 {
+        u16::from_ne_bytes([data[offset], data[offset + 1]])
+    }
+
+    fn read_u32(data: &[u8], offset: usize) -> u32 {
+        u32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn read_u64(data: &[u8], offset: usize) -> u64 {
+        u64::from_ne_bytes(data[offset..offset + 8].try_into().unwrap())
+    }
+}
@@ -0,0 +1,111 @@
+//! NUMA topology and memory placement from sysfs and procfs on Linux.
+
+use sysinfo::Pid;
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const ROOT: &str = "/sys/devices/system/node";
+
+/// A NUMA node: a socket or die with its own memory.
+#[derive(Debug, Clone, Default)]
+pub struct Node {
+    pub id: u32,
+    /// Indices of the CPUs of the node.
+    pub cpus: Vec<usize>,
+    /// Memory of the node in bytes.
+    pub total_memory: u64,
+    pub used_memory: u64,
+}
+
+/// The NUMA nodes of the machine, sorted by id. Empty where sysfs does not describe them.
+#[cfg(target_os = "linux")]
+pub fn nodes() -> Vec<Node> {
+    let Ok(entries) = std::fs::read_dir(ROOT) else {
+        return vec![];
+    };
+    let mut nodes: Vec<Node> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let id = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("node")?
+                .parse()
+                .ok()?;
+            let dir = entry.path();
+            let cpus = std::fs::read_to_string(dir.join("cpulist")).ok()?;
+            let meminfo = std::fs::read_to_string(dir.join("meminfo")).unwrap_or_default();
+            let field = |name: &str| {
+                meminfo.lines().find_map(|line| {
+                    // Like `Node 0 MemTotal:  16384 kB`.
+                    let (key, value) = line.split_once(':')?;
+                    key.ends_with(name)
+                        .then(|| value.trim().strip_suffix("kB")?.trim().parse::<u64>().ok())
+                        .flatten()
+                        .map(|kb| kb * 1024)
+                })
+            };
+            Some(Node {
+                id,
+                cpus: parse_list(cpus.trim()),
+                total_memory: field("MemTotal").unwrap_or(0),
+                used_memory: field("MemUsed").unwrap_or(0),
+            })
+        })
+        .collect();
+    nodes.sort_by_key(|node| node.id);
+    nodes
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn nodes() -> Vec<Node> {
+    vec![]
+}
+
+/// The node holding most of the resident memory of a process, from `/proc/<pid>/numa_maps`.
+#[cfg(target_os = "linux")]
+pub fn memory_node(pid: Pid) -> Option<u64> {
+    let text = std::fs::read_to_string(format!("/proc/{pid}/numa_maps")).ok()?;
+    let mut pages: Vec<u64> = vec![];
+    // Mappings list their pages per node like `N0=12 N1=3`.
+    for field in text.split_whitespace() {
+        let Some((node, count)) = field
+            .strip_prefix('N')
+            .and_then(|field| field.split_once('='))
+        else {
+            continue;
+        };
+        let (Ok(node), Ok(count)) = (node.parse::<usize>(), count.parse::<u64>()) else {
+            continue;
+        };
+        if pages.len() <= node {
+            pages.resize(node + 1, 0);
+        }
+        pages[node] += count;
+    }
+    let (node, _) = pages
+        .iter()
+        .enumerate()
+        .filter(|&(_, &count)| count > 0)
+        .max_by_key(|&(_, &count)| count)?;
+    Some(node as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn memory_node(_pid: Pid) -> Option<u64> {
+    None
+}
+
+/// Parse a CPU list like `0-3,8-11`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_list(list: &str) -> Vec<usize> {
+    list.split(',')
+        .filter_map(|range| match range.split_once('-') {
+            Some((start, end)) => Some(start.parse().ok()?..=end.parse().ok()?),
+            None => {
+                let cpu = range.parse().ok()?;
+                Some(cpu..=cpu)
+            }
+        })
+        .flatten()
+        .collect()
+}
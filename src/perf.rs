@@ -0,0 +1,48 @@
+//! Timings of ratatop itself for the debug overlay, so slowdowns in sampling and drawing can
+//! be measured.
+
+use std::{collections::VecDeque, time::Duration};
+
+/// Number of measurements the averages and maxima cover.
+const WINDOW: usize = 60;
+
+/// Recent durations of one step.
+#[derive(Debug, Default)]
+pub struct Timing {
+    recent: VecDeque<Duration>,
+}
+
+impl Timing {
+    pub fn record(&mut self, duration: Duration) {
+        self.recent.push_back(duration);
+        if self.recent.len() > WINDOW {
+            self.recent.pop_front();
+        }
+    }
+
+    pub fn last(&self) -> Duration {
+        self.recent.back().copied().unwrap_or_default()
+    }
+
+    pub fn average(&self) -> Duration {
+        let total: Duration = self.recent.iter().sum();
+        total / self.recent.len().max(1) as u32
+    }
+
+    pub fn max(&self) -> Duration {
+        self.recent.iter().max().copied().unwrap_or_default()
+    }
+}
+
+/// What the debug overlay shows.
+#[derive(Debug, Default)]
+pub struct Perf {
+    /// From the start of one frame to the start of the next, including waiting for input.
+    pub frame: Timing,
+    /// Building the widgets of a frame.
+    pub draw: Timing,
+    /// Refreshing the processes from sysinfo and procfs.
+    pub refresh: Timing,
+    /// Process table rows drawn in the last frame.
+    pub rows: usize,
+}
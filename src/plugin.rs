@@ -0,0 +1,157 @@
+use std::{
+    process::Command,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::Duration,
+};
+
+use crate::history::{History, Retention};
+
+/// A command panel from a `[panel.NAME]` config section.
+///
+/// The panel's data comes from an external command run every `interval` by `sh -c`, so any
+/// metric a script can print (JVM stats, application counters, ...) can be shown without
+/// changing ratatop. The commands run with the user's privileges and are not sandboxed.
+/// There is no scripting API in Lua or WASM, which would need an embedded runtime.
+#[derive(Debug, Clone)]
+pub struct PanelConfig {
+    pub name: String,
+    pub command: String,
+    pub interval: Duration,
+    pub kind: PanelKind,
+    /// History kept by chart panels.
+    pub history: Retention,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanelKind {
+    /// Every output line is a row, its cells separated by tabs or whitespace.
+    #[default]
+    Table,
+    /// The first number on the last output line is plotted.
+    Chart,
+}
+
+impl PanelKind {
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "table" => Some(PanelKind::Table),
+            "chart" => Some(PanelKind::Chart),
+            _ => None,
+        }
+    }
+}
+
+impl PanelConfig {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            command: String::new(),
+            interval: Duration::from_secs(5),
+            kind: PanelKind::default(),
+            history: Retention::default(),
+        }
+    }
+}
+
+/// What a panel currently shows.
+#[derive(Debug, Clone)]
+pub enum PanelData {
+    /// The command has not finished yet.
+    Pending,
+    Table(Vec<Vec<String>>),
+    /// The samples are in [`Panel::history`].
+    Chart,
+    Error(String),
+}
+
+/// A running panel: a collector thread and the latest data it produced.
+#[derive(Debug)]
+pub struct Panel {
+    pub config: PanelConfig,
+    pub data: PanelData,
+    /// Samples of a chart panel, kept when the command fails in between.
+    pub history: History,
+    outputs: Receiver<Result<String, String>>,
+    /// Dropped with the panel to tell the collector thread to stop. The thread is not
+    /// joined, so quitting does not wait for a command that hangs.
+    _stop: Sender<()>,
+}
+
+impl Panel {
+    /// Start the collector thread of the panel.
+    pub fn spawn(config: PanelConfig) -> Self {
+        let (sender, outputs) = mpsc::channel();
+        let (stop, stopped) = mpsc::channel::<()>();
+        let command = config.command.clone();
+        let interval = config.interval;
+        thread::spawn(move || loop {
+            if sender.send(run(&command)).is_err() {
+                break;
+            }
+            // Waiting on the stop channel instead of sleeping ends the thread right away.
+            if stopped.recv_timeout(interval) != Err(RecvTimeoutError::Timeout) {
+                break;
+            }
+        });
+        Self {
+            history: History::new(config.history),
+            config,
+            data: PanelData::Pending,
+            outputs,
+            _stop: stop,
+        }
+    }
+
+    /// Take the outputs collected since the last call. `x` is the chart coordinate of new
+    /// samples.
+    pub fn collect(&mut self, x: f64) {
+        while let Ok(output) = self.outputs.try_recv() {
+            self.data = match (output, self.config.kind) {
+                (Err(err), _) => PanelData::Error(err),
+                (Ok(text), PanelKind::Table) => PanelData::Table(
+                    text.lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .map(split_cells)
+                        .collect(),
+                ),
+                (Ok(text), PanelKind::Chart) => {
+                    let value = text
+                        .lines()
+                        .rev()
+                        .find_map(|line| line.split_whitespace().next())
+                        .and_then(|value| value.parse::<f64>().ok());
+                    match value {
+                        Some(value) => {
+                            self.history.push(x, value);
+                            PanelData::Chart
+                        }
+                        None => PanelData::Error(format!("not a number: {}", text.trim())),
+                    }
+                }
+            };
+        }
+    }
+}
+
+fn run(command: &str) -> Result<String, String> {
+    let output = Command::new("sh")
+        .args(["-c", command])
+        .output()
+        .map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{}: {}", output.status, stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn split_cells(line: &str) -> Vec<String> {
+    if line.contains('\t') {
+        line.split('\t')
+            .map(|cell| cell.trim().to_string())
+            .collect()
+    } else {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+}
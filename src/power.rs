@@ -0,0 +1,133 @@
+//! Power draw from the RAPL energy counters in `/sys/class/powercap` on Linux, which Intel
+//! and recent AMD CPUs provide for the package, its cores and often the memory.
+
+use std::{path::PathBuf, time::Instant};
+
+use crate::history::{History, Retention};
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const ROOT: &str = "/sys/class/powercap";
+
+/// A RAPL power domain like `package-0` or `package-0 core`.
+#[derive(Debug)]
+pub struct Zone {
+    pub label: String,
+    /// Watts.
+    pub history: History,
+    dir: PathBuf,
+    /// The counter wraps around after this many microjoules.
+    max_energy: u64,
+    /// Counter and time of the last reading.
+    last: Option<(u64, Instant)>,
+}
+
+#[derive(Debug, Default)]
+pub struct Power {
+    pub zones: Vec<Zone>,
+    /// Why the counters could not be read. They are only readable by root on many systems.
+    pub error: Option<String>,
+}
+
+impl Power {
+    pub fn new(retention: Retention) -> Self {
+        Self {
+            zones: zones(retention),
+            error: None,
+        }
+    }
+
+    /// Read the counters, `x` being the seconds since ratatop started. The first reading
+    /// only sets the baseline.
+    pub fn sample(&mut self, x: f64) {
+        for zone in &mut self.zones {
+            let energy = match std::fs::read_to_string(zone.dir.join("energy_uj")) {
+                Ok(text) => text.trim().parse::<u64>().ok(),
+                Err(err) => {
+                    self.error = Some(err.to_string());
+                    None
+                }
+            };
+            let Some(energy) = energy else {
+                continue;
+            };
+            let now = Instant::now();
+            if let Some((last_energy, last_time)) = zone.last {
+                let used = if energy >= last_energy {
+                    energy - last_energy
+                } else {
+                    zone.max_energy - last_energy + energy
+                };
+                let seconds = now.duration_since(last_time).as_secs_f64();
+                if seconds > 0.0 {
+                    zone.history.push(x, used as f64 / 1e6 / seconds);
+                }
+            }
+            zone.last = Some((energy, now));
+        }
+    }
+
+    /// Highest draw of any zone, for the chart bounds.
+    pub fn peak(&self) -> Option<f64> {
+        self.zones
+            .iter()
+            .filter_map(|zone| zone.history.peak())
+            .max_by(f64::total_cmp)
+    }
+
+    pub fn x_bounds(&self) -> [f64; 2] {
+        self.zones
+            .iter()
+            .map(|zone| zone.history.x_bounds())
+            .reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])])
+            .unwrap_or([0.0, 0.0])
+    }
+}
+
+/// The RAPL zones and their subzones, like `intel-rapl:0` and `intel-rapl:0:0`.
+#[cfg(target_os = "linux")]
+fn zones(retention: Retention) -> Vec<Zone> {
+    let Ok(entries) = std::fs::read_dir(ROOT) else {
+        return vec![];
+    };
+    let mut dirs: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            // The MMIO interface duplicates the MSR one.
+            name.starts_with("intel-rapl:")
+                .then(|| (name, entry.path()))
+        })
+        .collect();
+    dirs.sort();
+    let read = |dir: &PathBuf, file: &str| {
+        std::fs::read_to_string(dir.join(file))
+            .map(|text| text.trim().to_string())
+            .unwrap_or_default()
+    };
+    dirs.iter()
+        .map(|(name, dir)| {
+            let mut label = read(dir, "name");
+            // Subzones are named like `core`, so add the package they belong to.
+            if let Some((parent, _)) = name
+                .rsplit_once(':')
+                .filter(|_| name.matches(':').count() > 1)
+            {
+                if let Some((_, parent_dir)) = dirs.iter().find(|(name, _)| name == parent) {
+                    label = format!("{} {label}", read(parent_dir, "name"));
+                }
+            }
+            Zone {
+                label,
+                history: History::new(retention),
+                dir: dir.clone(),
+                max_energy: read(dir, "max_energy_range_uj").parse().unwrap_or(u64::MAX),
+                last: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn zones(_retention: Retention) -> Vec<Zone> {
+    vec![]
+}
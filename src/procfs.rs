@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use sysinfo::Pid;
+
+/// Per-process details from `/proc/<pid>/status` that sysinfo does not provide.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProcStatus {
+    /// Swapped out memory in bytes.
+    pub swap: Option<u64>,
+    pub threads: Option<u64>,
+    pub voluntary_ctxt_switches: Option<u64>,
+    pub nonvoluntary_ctxt_switches: Option<u64>,
+    /// Bytes sent and received per second over TCP, filled in from
+    /// [`crate::network::NetworkMonitor`] while a column shows it.
+    pub network: Option<u64>,
+    /// NUMA node holding most of the memory, filled in from [`crate::numa::memory_node`]
+    /// while a column shows it.
+    pub numa_node: Option<u64>,
+    /// Open kernel object handles, Windows only.
+    pub handles: Option<u64>,
+    /// Jail ID, FreeBSD only.
+    pub jail: Option<u64>,
+    /// Device number of the controlling terminal, filled in from [`tty`] while a column
+    /// shows it.
+    pub tty: Option<u64>,
+}
+
+impl ProcStatus {
+    #[cfg(target_os = "linux")]
+    pub fn read(pid: Pid) -> Option<Self> {
+        let text = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+        Some(Self::parse(&text))
+    }
+
+    #[cfg(windows)]
+    pub fn read(pid: Pid) -> Option<Self> {
+        Some(Self {
+            handles: Some(crate::windows::handle_count(pid)?),
+            ..Self::default()
+        })
+    }
+
+    #[cfg(target_os = "freebsd")]
+    pub fn read(pid: Pid) -> Option<Self> {
+        Some(Self {
+            jail: Some(crate::jail::jail_of(pid)?),
+            ..Self::default()
+        })
+    }
+
+    /// Only procfs provides these details.
+    #[cfg(not(any(target_os = "linux", target_os = "freebsd", windows)))]
+    pub fn read(_pid: Pid) -> Option<Self> {
+        None
+    }
+
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn parse(text: &str) -> Self {
+        let mut status = Self::default();
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key {
+                "VmSwap" => status.swap = parse_kb(value),
+                "Threads" => status.threads = value.parse().ok(),
+                "voluntary_ctxt_switches" => status.voluntary_ctxt_switches = value.parse().ok(),
+                "nonvoluntary_ctxt_switches" => {
+                    status.nonvoluntary_ctxt_switches = value.parse().ok();
+                }
+                _ => {}
+            }
+        }
+        status
+    }
+}
+
+/// Read the status of every process in `pids`, skipping processes that disappeared.
+pub fn read_all(pids: impl Iterator<Item = Pid>) -> HashMap<Pid, ProcStatus> {
+    pids.filter_map(|pid| ProcStatus::read(pid).map(|status| (pid, status)))
+        .collect()
+}
+
+/// Parse a value like `1234 kB` into bytes.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_kb(value: &str) -> Option<u64> {
+    let kb: u64 = value.strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Highest clock frequency of the CPUs in MHz, from cpufreq.
+#[cfg(target_os = "linux")]
+pub fn max_frequency() -> Option<u64> {
+    let path = "/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq";
+    let khz: u64 = std::fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    Some(khz / 1000)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn max_frequency() -> Option<u64> {
+    None
+}
+
+/// Device number of the controlling terminal, 0 for processes without one, from
+/// `/proc/<pid>/stat`.
+#[cfg(target_os = "linux")]
+pub fn tty(pid: Pid) -> Option<u64> {
+    let text = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The name in parentheses may contain spaces; state, ppid, pgrp, session and tty follow.
+    let (_, fields) = text.rsplit_once(')')?;
+    fields.split_whitespace().nth(4)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn tty(_pid: Pid) -> Option<u64> {
+    None
+}
+
+/// Name of a terminal device like `pts/3` or `tty1`, as `ps` shows it.
+pub fn tty_name(device: u64) -> String {
+    let major = (device >> 8) & 0xfff;
+    let minor = (device & 0xff) | ((device >> 12) & 0xfff00);
+    match major {
+        136..=143 => format!("pts/{}", (major - 136) * 256 + minor),
+        4 if minor < 64 => format!("tty{minor}"),
+        4 => format!("ttyS{}", minor - 64),
+        5 if minor == 0 => "tty".to_string(),
+        5 if minor == 1 => "console".to_string(),
+        _ => format!("{major}:{minor}"),
+    }
+}
+
+/// A mapped memory region of a process, from `/proc/<pid>/smaps`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub start: u64,
+    pub end: u64,
+    /// Like `r-xp`.
+    pub permissions: String,
+    /// Backing file or pseudo path like `[heap]`, empty for anonymous memory.
+    pub path: String,
+    /// Resident part of the region in bytes, if known.
+    pub rss: Option<u64>,
+}
+
+impl MemoryRegion {
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+}
+
+/// Memory regions of a process, with their resident size when `smaps` is readable.
+#[cfg(target_os = "linux")]
+pub fn memory_maps(pid: Pid) -> std::io::Result<Vec<MemoryRegion>> {
+    match std::fs::read_to_string(format!("/proc/{pid}/smaps")) {
+        Ok(text) => Ok(parse_maps(&text)),
+        Err(_) => {
+            std::fs::read_to_string(format!("/proc/{pid}/maps")).map(|text| parse_maps(&text))
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn memory_maps(_pid: Pid) -> std::io::Result<Vec<MemoryRegion>> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// Parse `maps` or `smaps`: region lines like
+/// `7f12a000-7f12c000 r-xp 00000000 08:01 1234  /usr/lib/libc.so.6`, in `smaps` followed by
+/// `Key: value` lines.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_maps(text: &str) -> Vec<MemoryRegion> {
+    let mut regions: Vec<MemoryRegion> = vec![];
+    for line in text.lines() {
+        if let Some(value) = line.strip_prefix("Rss:") {
+            if let Some(region) = regions.last_mut() {
+                region.rss = parse_kb(value.trim());
+            }
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let (Some(range), Some(permissions)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let Some((start, end)) = range.split_once('-').and_then(|(start, end)| {
+            Some((
+                u64::from_str_radix(start, 16).ok()?,
+                u64::from_str_radix(end, 16).ok()?,
+            ))
+        }) else {
+            continue;
+        };
+        // Skip offset, device and inode; the path may contain spaces.
+        let path = fields.skip(3).collect::<Vec<_>>().join(" ");
+        regions.push(MemoryRegion {
+            start,
+            end,
+            permissions: permissions.to_string(),
+            path,
+            rss: None,
+        });
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(start: u64, end: u64, permissions: &str, path: &str) -> MemoryRegion {
+        MemoryRegion {
+            start,
+            end,
+            permissions: permissions.to_string(),
+            path: path.to_string(),
+            rss: None,
+        }
+    }
+
+    #[test]
+    fn parse_maps_paths() {
+        let text = "\
+55d4a000-55d4c000 r-xp 00000000 08:01 1234                       /usr/bin/cat
+55d6b000-55d8c000 rw-p 00000000 00:00 0                          [heap]
+7f12a000-7f12c000 rw-p 00000000 00:00 0
+7f12c000-7f12d000 r--p 00000000 08:01 5678                       /home/me/My Files/lib.so
+7f12d000-7f12e000 r-xp 00000000 08:01 9012                       /tmp/old.so (deleted)
+7ffd1000-7ffd2000 rw-p 00000000 00:00 0                          [stack]
+";
+        assert_eq!(
+            parse_maps(text),
+            vec![
+                region(0x55d4a000, 0x55d4c000, "r-xp", "/usr/bin/cat"),
+                region(0x55d6b000, 0x55d8c000, "rw-p", "[heap]"),
+                region(0x7f12a000, 0x7f12c000, "rw-p", ""),
+                region(0x7f12c000, 0x7f12d000, "r--p", "/home/me/My Files/lib.so"),
+                region(0x7f12d000, 0x7f12e000, "r-xp", "/tmp/old.so (deleted)"),
+                region(0x7ffd1000, 0x7ffd2000, "rw-p", "[stack]"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_smaps_rss() {
+        let text = "\
+55d6b000-55d8c000 rw-p 00000000 00:00 0                          [heap]
+Size:                132 kB
+Rss:                  84 kB
+Pss:                  84 kB
+7f12a000-7f12c000 rw-p 00000000 00:00 0
+Size:                  8 kB
+";
+        let regions = parse_maps(text);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].rss, Some(84 * 1024));
+        assert_eq!(regions[0].size(), 0x21000);
+        assert_eq!(regions[1].rss, None);
+    }
+
+    /// Encode a device number like the kernel's `new_encode_dev` does for `/proc/<pid>/stat`.
+    fn device(major: u64, minor: u64) -> u64 {
+        (minor & 0xff) | (major << 8) | ((minor & !0xff) << 12)
+    }
+
+    #[test]
+    fn pseudo_terminals() {
+        assert_eq!(tty_name(device(136, 3)), "pts/3");
+        assert_eq!(tty_name(device(136, 300)), "pts/300");
+        assert_eq!(tty_name(device(137, 2)), "pts/258");
+    }
+
+    #[test]
+    fn terminals() {
+        assert_eq!(tty_name(device(4, 1)), "tty1");
+        assert_eq!(tty_name(device(4, 63)), "tty63");
+        assert_eq!(tty_name(device(4, 64)), "ttyS0");
+        assert_eq!(tty_name(device(4, 65)), "ttyS1");
+        assert_eq!(tty_name(device(5, 0)), "tty");
+        assert_eq!(tty_name(device(5, 1)), "console");
+    }
+
+    #[test]
+    fn unknown_devices() {
+        assert_eq!(tty_name(device(204, 64)), "204:64");
+        assert_eq!(tty_name(device(5, 2)), "5:2");
+    }
+}
@@ -0,0 +1,262 @@
+//! Pushing metrics to InfluxDB or Graphite, built with the `push` feature, so ratatop can
+//! feed an existing Grafana setup.
+//!
+//! Samples are sent by a background thread so a slow or unreachable server never stalls
+//! the interface.
+
+use std::{
+    fmt::Write as _,
+    io::Write,
+    net::{TcpStream, ToSocketAddrs, UdpSocket},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use sysinfo::System;
+
+use crate::metrics::Sample;
+
+/// How long to wait for a TCP connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Settings from the `[push]` section of the config.
+#[derive(Debug, Clone)]
+pub struct PushConfig {
+    pub protocol: Protocol,
+    /// `host:port` of the server.
+    pub address: String,
+    pub transport: Transport,
+    pub interval: Duration,
+    /// Measurement name for InfluxDB, first path component for Graphite.
+    pub prefix: String,
+    /// How many of the busiest processes are sent along.
+    pub top: usize,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            protocol: Protocol::Influx,
+            address: String::new(),
+            transport: Transport::Tcp,
+            interval: Duration::from_secs(10),
+            prefix: "ratatop".to_string(),
+            top: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// InfluxDB line protocol.
+    Influx,
+    /// Graphite plaintext protocol.
+    Graphite,
+}
+
+impl Protocol {
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "influx" | "influxdb" => Some(Protocol::Influx),
+            "graphite" => Some(Protocol::Graphite),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Tcp,
+    Udp,
+}
+
+impl Transport {
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "tcp" => Some(Transport::Tcp),
+            "udp" => Some(Transport::Udp),
+            _ => None,
+        }
+    }
+}
+
+/// Hands a [`Sample`] to the sender thread every [`PushConfig::interval`].
+#[derive(Debug)]
+pub struct Pusher {
+    interval: Duration,
+    top: usize,
+    last: Option<Instant>,
+    samples: Sender<Sample>,
+    /// Failures of the sender thread, reported once until a push succeeds again.
+    errors: Receiver<String>,
+}
+
+impl Pusher {
+    /// Start the sender thread.
+    pub fn spawn(config: PushConfig) -> Self {
+        let (samples, receiver) = mpsc::channel::<Sample>();
+        let (error_sender, errors) = mpsc::channel();
+        let interval = config.interval;
+        let top = config.top;
+        thread::spawn(move || {
+            let host = System::host_name().unwrap_or_default();
+            let mut failing = false;
+            for sample in receiver {
+                let text = match config.protocol {
+                    Protocol::Influx => influx(&sample, &config.prefix, &host),
+                    Protocol::Graphite => graphite(&sample, &config.prefix, &host),
+                };
+                match send(&config, text.as_bytes()) {
+                    Ok(()) => failing = false,
+                    Err(err) if !failing => {
+                        failing = true;
+                        let message = format!("{}: {err}", config.address);
+                        if error_sender.send(message).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => {}
+                }
+            }
+        });
+        Self {
+            interval,
+            top,
+            last: None,
+            samples,
+            errors,
+        }
+    }
+
+    /// Queue a sample if the interval has passed since the last one.
+    pub fn push(&mut self, system: &System) {
+        let now = Instant::now();
+        if self
+            .last
+            .is_some_and(|last| now.duration_since(last) < self.interval)
+        {
+            return;
+        }
+        self.last = Some(now);
+        let _ = self.samples.send(Sample::collect(system, self.top));
+    }
+
+    /// A failure of the sender thread since the last call, if any.
+    pub fn error(&self) -> Option<String> {
+        self.errors.try_recv().ok()
+    }
+}
+
+fn send(config: &PushConfig, data: &[u8]) -> std::io::Result<()> {
+    let address = config
+        .address
+        .to_socket_addrs()?
+        .next()
+        .ok_or(std::io::ErrorKind::AddrNotAvailable)?;
+    match config.transport {
+        Transport::Tcp => {
+            let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+            stream.write_all(data)
+        }
+        Transport::Udp => {
+            let bind = if address.is_ipv4() {
+                "0.0.0.0:0"
+            } else {
+                "[::]:0"
+            };
+            UdpSocket::bind(bind)?.send_to(data, address).map(|_| ())
+        }
+    }
+}
+
+/// The sample in InfluxDB line protocol: one line for the system and one per process.
+fn influx(sample: &Sample, measurement: &str, host: &str) -> String {
+    let nanoseconds = (sample.timestamp * 1e9) as u64;
+    let host = influx_tag(host);
+    let mut text = String::new();
+    let _ = writeln!(
+        text,
+        "{measurement},host={host} cpu_usage={:.2},used_memory={}i,total_memory={}i,\
+         used_swap={}i,load1={:.2},load5={:.2},load15={:.2} {nanoseconds}",
+        sample.cpu,
+        sample.used_memory,
+        sample.total_memory,
+        sample.used_swap,
+        sample.load[0],
+        sample.load[1],
+        sample.load[2],
+    );
+    for process in &sample.top {
+        let _ = writeln!(
+            text,
+            "{measurement}_process,host={host},name={},pid={} cpu_usage={:.2},memory={}i \
+             {nanoseconds}",
+            influx_tag(&process.name),
+            process.pid,
+            process.cpu,
+            process.memory,
+        );
+    }
+    text
+}
+
+/// Escape the characters with a meaning in tag values.
+fn influx_tag(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    if escaped.is_empty() {
+        escaped.push_str("unknown");
+    }
+    escaped
+}
+
+/// The sample in the Graphite plaintext protocol, like
+/// `ratatop.myhost.processes.postgres.cpu_usage 12.50 1700000000`.
+fn graphite(sample: &Sample, prefix: &str, host: &str) -> String {
+    let seconds = sample.timestamp as u64;
+    let base = format!("{prefix}.{}", graphite_name(host));
+    let mut text = String::new();
+    let metrics = [
+        ("cpu_usage", format!("{:.2}", sample.cpu)),
+        ("used_memory", sample.used_memory.to_string()),
+        ("total_memory", sample.total_memory.to_string()),
+        ("used_swap", sample.used_swap.to_string()),
+        ("load1", format!("{:.2}", sample.load[0])),
+        ("load5", format!("{:.2}", sample.load[1])),
+        ("load15", format!("{:.2}", sample.load[2])),
+    ];
+    for (name, value) in metrics {
+        let _ = writeln!(text, "{base}.{name} {value} {seconds}");
+    }
+    for process in &sample.top {
+        let path = format!("{base}.processes.{}", graphite_name(&process.name));
+        let _ = writeln!(text, "{path}.cpu_usage {:.2} {seconds}", process.cpu);
+        let _ = writeln!(text, "{path}.memory {} {seconds}", process.memory);
+    }
+    text
+}
+
+/// A metric path component: dots and other separators become underscores.
+fn graphite_name(value: &str) -> String {
+    let name: String = value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "unknown".to_string()
+    } else {
+        name
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt::Write as _;
+
+use color_eyre::Result;
+use sysinfo::{Pid, Process, System, Users};
+
+use crate::summary::Summary;
+
+/// Output format of `--once`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl Format {
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Collect a single snapshot of the system and its processes and print it to stdout,
+/// for scripts and health checks. Only the processes in `pids` are listed, if any.
+pub fn print(format: Format, pids: &[Pid]) -> Result<()> {
+    let mut system = System::new_all();
+    // CPU usage is computed from the difference between two refreshes.
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    system.refresh_all();
+    let users = Users::new_with_refreshed_list();
+
+    let mut processes: Vec<&Process> = system
+        .processes()
+        .values()
+        .filter(|process| pids.is_empty() || pids.contains(&process.pid()))
+        .collect();
+    processes.sort_by_key(|process| process.pid());
+    let user = |process: &Process| {
+        process
+            .user_id()
+            .and_then(|uid| users.get_user_by_id(uid))
+            .map(|user| user.name().to_string())
+            .unwrap_or_default()
+    };
+
+    let text = match format {
+        Format::Text => text(&system, &processes, user)?,
+        Format::Json => json(&system, &processes, user)?,
+    };
+    print!("{text}");
+    Ok(())
+}
+
+fn text(
+    system: &System,
+    processes: &[&Process],
+    user: impl Fn(&Process) -> String,
+) -> Result<String> {
+    let summary = Summary::collect(system);
+    let mut text = String::new();
+    writeln!(
+        text,
+        "cpu {:.1}%  memory {}/{} MiB  processes {} (running {}, sleeping {}, zombie {})",
+        system.global_cpu_usage(),
+        system.used_memory() / 1024 / 1024,
+        system.total_memory() / 1024 / 1024,
+        summary.total,
+        summary.running,
+        summary.sleeping,
+        summary.zombie,
+    )?;
+    writeln!(
+        text,
+        "{:>8} {:>8} {:<12} {:>6} {:>10} {:<10} NAME",
+        "PID", "PPID", "USER", "CPU%", "MEM(KiB)", "STATUS"
+    )?;
+    for process in processes {
+        writeln!(
+            text,
+            "{:>8} {:>8} {:<12} {:>6.1} {:>10} {:<10} {}",
+            process.pid().as_u32(),
+            process
+                .parent()
+                .map_or_else(String::new, |pid| pid.to_string()),
+            user(process),
+            process.cpu_usage(),
+            process.memory() / 1024,
+            process.status().to_string(),
+            process.name().to_string_lossy(),
+        )?;
+    }
+    Ok(text)
+}
+
+fn json(
+    system: &System,
+    processes: &[&Process],
+    user: impl Fn(&Process) -> String,
+) -> Result<String> {
+    let summary = Summary::collect(system);
+    let load = System::load_average();
+    let mut text = String::from("{\n  \"system\": {\n");
+    writeln!(
+        text,
+        "    \"hostname\": {},",
+        string(&System::host_name().unwrap_or_default())
+    )?;
+    writeln!(text, "    \"uptime\": {},", System::uptime())?;
+    writeln!(text, "    \"cpu_usage\": {:.2},", system.global_cpu_usage())?;
+    writeln!(text, "    \"cpu_count\": {},", system.cpus().len())?;
+    writeln!(
+        text,
+        "    \"load_average\": [{:.2}, {:.2}, {:.2}],",
+        load.one, load.five, load.fifteen
+    )?;
+    writeln!(text, "    \"total_memory\": {},", system.total_memory())?;
+    writeln!(text, "    \"used_memory\": {},", system.used_memory())?;
+    writeln!(text, "    \"total_swap\": {},", system.total_swap())?;
+    writeln!(text, "    \"used_swap\": {},", system.used_swap())?;
+    writeln!(
+        text,
+        "    \"processes\": {{\"total\": {}, \"running\": {}, \"sleeping\": {}, \"zombie\": {}}}",
+        summary.total, summary.running, summary.sleeping, summary.zombie
+    )?;
+    text.push_str("  },\n  \"processes\": [");
+    for (index, process) in processes.iter().enumerate() {
+        if index > 0 {
+            text.push(',');
+        }
+        let cmd: Vec<_> = process
+            .cmd()
+            .iter()
+            .map(|arg| string(&arg.to_string_lossy()))
+            .collect();
+        write!(
+            text,
+            "\n    {{\"pid\": {}, \"ppid\": {}, \"name\": {}, \"user\": {}, \"status\": {}, \
+             \"cpu_usage\": {:.2}, \"memory\": {}, \"virtual_memory\": {}, \"cmd\": [{}]}}",
+            process.pid(),
+            process
+                .parent()
+                .map_or_else(|| "null".to_string(), |pid| pid.to_string()),
+            string(&process.name().to_string_lossy()),
+            string(&user(process)),
+            string(&process.status().to_string()),
+            process.cpu_usage(),
+            process.memory(),
+            process.virtual_memory(),
+            cmd.join(", "),
+        )?;
+    }
+    text.push_str("\n  ]\n}\n");
+    Ok(text)
+}
+
+/// A JSON string literal.
+pub fn string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use sysinfo::{Pid, Process, System, Users};
+
+use crate::{
+    columns::{Column, ProcessInfo},
+    energy::Energy,
+    filter::Filter,
+    procfs::ProcStatus,
+    tree,
+};
+
+/// The view settings the order of the process table depends on.
+///
+/// Rows are rebuilt only when the key changes, i.e. after a process refresh or when the user
+/// changes the filter or sorting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowsKey {
+    /// Incremented on every process refresh.
+    pub generation: u64,
+    /// Search query, see [`Filter`].
+    pub query: String,
+    pub sort_column: Column,
+    pub sort_descending: bool,
+    pub tree: bool,
+    /// Only include processes of this systemd unit.
+    pub unit: Option<String>,
+    /// Only include these processes, if any.
+    pub pids: Vec<Pid>,
+    /// Processes shown above all others, see [`Pins`].
+    pub pins: Pins,
+}
+
+/// Processes pinned to the top of the table, by PID or by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pins {
+    pub pids: Vec<Pid>,
+    /// Lowercase substrings of process names, `names` in the `[pins]` section of the config.
+    pub names: Vec<String>,
+}
+
+impl Pins {
+    pub fn contains(&self, process: &Process) -> bool {
+        if self.pids.contains(&process.pid()) {
+            return true;
+        }
+        if self.names.is_empty() {
+            return false;
+        }
+        let name = process.name().to_string_lossy().to_lowercase();
+        self.names
+            .iter()
+            .any(|pattern| name.contains(pattern.as_str()))
+    }
+}
+
+/// A row of the process table. Cells are only rendered for rows in the viewport.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessRow {
+    pub pid: Pid,
+    /// Depth in tree mode, 0 otherwise.
+    pub depth: usize,
+    pub pinned: bool,
+}
+
+impl ProcessRow {
+    /// Text of the `columns` of this row. `branch` prefixes child processes in tree mode.
+    pub fn cells(self, info: ProcessInfo, columns: &[Column], branch: &str) -> Vec<String> {
+        columns
+            .iter()
+            .map(|column| {
+                let text = column.cell(info);
+                if *column == Column::Name && self.depth > 0 {
+                    format!("{}{branch}{text}", "  ".repeat(self.depth - 1))
+                } else {
+                    text
+                }
+            })
+            .collect()
+    }
+}
+
+/// Filter and sort the processes of `system` as described by `key`.
+pub fn build(
+    system: &System,
+    statuses: &HashMap<Pid, ProcStatus>,
+    energies: &HashMap<Pid, Energy>,
+    units: &HashMap<Pid, String>,
+    users: &Users,
+    key: &RowsKey,
+) -> Vec<ProcessRow> {
+    let filter = Filter::parse(&key.query);
+    let info = |process| ProcessInfo {
+        process,
+        status: statuses.get(&process.pid()),
+        energy: energies.get(&process.pid()),
+    };
+    let mut processes: Vec<&Process> = system
+        .processes()
+        .values()
+        .filter(|process| {
+            key.unit
+                .as_ref()
+                .is_none_or(|unit| units.get(&process.pid()) == Some(unit))
+        })
+        .filter(|process| key.pids.is_empty() || key.pids.contains(&process.pid()))
+        .filter(|process| filter.matches(info(process), users))
+        .collect();
+    processes.sort_by(|a, b| {
+        let ordering = key.sort_column.compare(info(a), info(b));
+        if key.sort_descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    // Pinned processes keep the sort order among themselves but leave the tree.
+    let (pinned, processes): (Vec<&Process>, Vec<&Process>) = processes
+        .into_iter()
+        .partition(|process| key.pins.contains(process));
+    let processes = if key.tree {
+        tree::tree_order(&processes)
+    } else {
+        processes.into_iter().map(|process| (process, 0)).collect()
+    };
+
+    let pinned = pinned.into_iter().map(|process| ProcessRow {
+        pid: process.pid(),
+        depth: 0,
+        pinned: true,
+    });
+    pinned
+        .chain(processes.into_iter().map(|(process, depth)| ProcessRow {
+            pid: process.pid(),
+            depth,
+            pinned: false,
+        }))
+        .collect()
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::{HashMap, HashSet},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use sysinfo::{Pid, Process, System, Users};
+
+use crate::{columns::ProcessInfo, energy::Energy, filter::Filter, procfs::ProcStatus};
+
+/// A `[rule.NAME]` config section: run `command` when a process has matched `condition`
+/// for `duration`.
+#[derive(Debug, Clone)]
+pub struct RuleConfig {
+    pub name: String,
+    /// A search query, usually a filter expression like `name ~ java && mem > 4096`.
+    pub condition: String,
+    pub duration: Duration,
+    /// Run by `sh -c` with `RATATOP_RULE`, `RATATOP_PID` and `RATATOP_NAME` set.
+    pub command: String,
+    /// Minimum time between two runs of the command.
+    pub cooldown: Duration,
+}
+
+impl RuleConfig {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            condition: String::new(),
+            duration: Duration::ZERO,
+            command: String::new(),
+            cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+/// A rule being evaluated by the sampling loop.
+#[derive(Debug)]
+pub struct Rule {
+    pub config: RuleConfig,
+    filter: Filter,
+    /// When each currently matching process started to match.
+    matching_since: HashMap<Pid, Instant>,
+    last_run: Option<Instant>,
+}
+
+/// A rule that fired.
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    pub rule: String,
+    pub pid: Pid,
+}
+
+impl Rule {
+    pub fn new(config: RuleConfig) -> Self {
+        Self {
+            // Always an expression: the config rejects conditions that are not.
+            filter: Filter::parse(&config.condition),
+            config,
+            matching_since: HashMap::new(),
+            last_run: None,
+        }
+    }
+
+    /// Check the processes of `system` against the rule and run its command for the first
+    /// process that matched long enough, unless the rule is cooling down.
+    pub fn evaluate(
+        &mut self,
+        system: &System,
+        statuses: &HashMap<Pid, ProcStatus>,
+        energies: &HashMap<Pid, Energy>,
+        users: &Users,
+    ) -> Option<Trigger> {
+        let now = Instant::now();
+        let matching: Vec<&Process> = system
+            .processes()
+            .values()
+            .filter(|process| {
+                let info = ProcessInfo {
+                    process,
+                    status: statuses.get(&process.pid()),
+                    energy: energies.get(&process.pid()),
+                };
+                self.filter.matches(info, users)
+            })
+            .collect();
+        let pids: HashSet<Pid> = matching.iter().map(|process| process.pid()).collect();
+        self.matching_since.retain(|pid, _| pids.contains(pid));
+        for process in &matching {
+            self.matching_since.entry(process.pid()).or_insert(now);
+        }
+
+        if self
+            .last_run
+            .is_some_and(|last_run| now.duration_since(last_run) < self.config.cooldown)
+        {
+            return None;
+        }
+        let process = matching
+            .into_iter()
+            .filter(|process| {
+                now.duration_since(self.matching_since[&process.pid()]) >= self.config.duration
+            })
+            .min_by_key(|process| process.pid())?;
+        self.last_run = Some(now);
+        self.run(process);
+        Some(Trigger {
+            rule: self.config.name.clone(),
+            pid: process.pid(),
+        })
+    }
+
+    /// Run the command in the background, so a slow command does not stall the interface.
+    fn run(&self, process: &Process) {
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", &self.config.command])
+            .env("RATATOP_RULE", &self.config.name)
+            .env("RATATOP_PID", process.pid().to_string())
+            .env("RATATOP_NAME", process.name())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        thread::spawn(move || command.status());
+    }
+}
@@ -0,0 +1,238 @@
+//! Hardware sensors: temperatures from hwmon on Linux and from sysinfo elsewhere, and fan
+//! speeds from hwmon. Other platforms do not expose fans without vendor interfaces like the
+//! macOS SMC.
+
+use crate::history::{History, Retention};
+
+/// A temperature sensor and its readings so far.
+#[derive(Debug)]
+pub struct Sensor {
+    pub label: String,
+    /// Temperature the hardware considers high, in °C.
+    pub warn: Option<f64>,
+    /// Temperature the hardware shuts down or throttles hard at, in °C.
+    pub critical: Option<f64>,
+    pub history: History,
+}
+
+/// The latest reading of a fan.
+#[derive(Debug, Clone)]
+pub struct Fan {
+    pub label: String,
+    pub rpm: u64,
+}
+
+/// A single reading of a temperature sensor.
+#[derive(Debug)]
+struct Reading {
+    label: String,
+    celsius: f64,
+    warn: Option<f64>,
+    critical: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Sensors {
+    /// In the order they were first seen.
+    pub temperatures: Vec<Sensor>,
+    pub fans: Vec<Fan>,
+    retention: Retention,
+    #[cfg(not(target_os = "linux"))]
+    components: sysinfo::Components,
+}
+
+impl Sensors {
+    pub fn new(retention: Retention) -> Self {
+        Self {
+            temperatures: vec![],
+            fans: vec![],
+            retention,
+            #[cfg(not(target_os = "linux"))]
+            components: sysinfo::Components::new_with_refreshed_list(),
+        }
+    }
+
+    /// Read all sensors, `x` being the seconds since ratatop started.
+    pub fn sample(&mut self, x: f64) {
+        for reading in self.read_temperatures() {
+            let index = match self
+                .temperatures
+                .iter()
+                .position(|sensor| sensor.label == reading.label)
+            {
+                Some(index) => index,
+                None => {
+                    self.temperatures.push(Sensor {
+                        label: reading.label,
+                        warn: None,
+                        critical: None,
+                        history: History::new(self.retention),
+                    });
+                    self.temperatures.len() - 1
+                }
+            };
+            let sensor = &mut self.temperatures[index];
+            sensor.warn = reading.warn;
+            sensor.critical = reading.critical;
+            sensor.history.push(x, reading.celsius);
+        }
+        self.fans = read_fans();
+    }
+
+    /// The lowest warning threshold of all sensors, where the chart draws its guide line.
+    pub fn warn(&self) -> Option<f64> {
+        self.temperatures
+            .iter()
+            .filter_map(|sensor| sensor.warn)
+            .min_by(f64::total_cmp)
+    }
+
+    /// The lowest critical threshold of all sensors.
+    pub fn critical(&self) -> Option<f64> {
+        self.temperatures
+            .iter()
+            .filter_map(|sensor| sensor.critical)
+            .min_by(f64::total_cmp)
+    }
+
+    /// The highest temperature seen by any sensor.
+    pub fn peak(&self) -> Option<f64> {
+        self.temperatures
+            .iter()
+            .filter_map(|sensor| sensor.history.peak())
+            .max_by(f64::total_cmp)
+    }
+
+    /// Earliest and latest sample time of all sensors.
+    pub fn x_bounds(&self) -> [f64; 2] {
+        self.temperatures
+            .iter()
+            .map(|sensor| sensor.history.x_bounds())
+            .reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])])
+            .unwrap_or([0.0, 0.0])
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read_temperatures(&mut self) -> Vec<Reading> {
+        hwmon::inputs("temp")
+            .into_iter()
+            .map(|input| {
+                let celsius = |value: f64| value / 1000.0;
+                Reading {
+                    celsius: celsius(input.value),
+                    warn: input.attribute("max").map(celsius),
+                    critical: input.attribute("crit").map(celsius),
+                    label: input.label,
+                }
+            })
+            .collect()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn read_temperatures(&mut self) -> Vec<Reading> {
+        self.components.refresh(true);
+        self.components
+            .list()
+            .iter()
+            .filter_map(|component| {
+                Some(Reading {
+                    label: component.label().to_string(),
+                    celsius: component.temperature()?.into(),
+                    warn: None,
+                    critical: component.critical().map(f64::from),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_fans() -> Vec<Fan> {
+    hwmon::inputs("fan")
+        .into_iter()
+        .map(|input| Fan {
+            label: input.label,
+            rpm: input.value as u64,
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_fans() -> Vec<Fan> {
+    vec![]
+}
+
+/// The Linux hardware monitoring interface in `/sys/class/hwmon`.
+#[cfg(target_os = "linux")]
+mod hwmon {
+    use std::path::{Path, PathBuf};
+
+    const ROOT: &str = "/sys/class/hwmon";
+
+    /// A `<kind><n>_input` file of a chip, like `temp1_input`.
+    #[derive(Debug)]
+    pub struct Input {
+        /// `<chip> <label>`, like `coretemp Core 0`, or `<chip> <kind><n>` without a label.
+        pub label: String,
+        pub value: f64,
+        /// The chip directory.
+        dir: PathBuf,
+        /// Like `temp1`.
+        stem: String,
+    }
+
+    impl Input {
+        /// Another numeric attribute of the input, like `max` for `temp1_max`.
+        pub fn attribute(&self, name: &str) -> Option<f64> {
+            read_number(&self.dir.join(format!("{}_{name}", self.stem)))
+        }
+    }
+
+    /// The inputs of all chips of a kind like `temp` or `fan`.
+    pub fn inputs(kind: &str) -> Vec<Input> {
+        let Ok(chips) = std::fs::read_dir(ROOT) else {
+            return vec![];
+        };
+        let mut chips: Vec<PathBuf> = chips.flatten().map(|entry| entry.path()).collect();
+        chips.sort();
+        let mut inputs = vec![];
+        for dir in chips {
+            let chip = std::fs::read_to_string(dir.join("name")).unwrap_or_default();
+            let Ok(files) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut stems: Vec<String> = files
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().into_string().ok()?;
+                    let stem = name.strip_suffix("_input")?;
+                    let number = stem.strip_prefix(kind)?;
+                    let numbered =
+                        !number.is_empty() && number.bytes().all(|byte| byte.is_ascii_digit());
+                    numbered.then(|| stem.to_string())
+                })
+                .collect();
+            // temp2 before temp10.
+            stems.sort_by_key(|stem| (stem.len(), stem.clone()));
+            for stem in stems {
+                let Some(value) = read_number(&dir.join(format!("{stem}_input"))) else {
+                    continue;
+                };
+                let label = std::fs::read_to_string(dir.join(format!("{stem}_label")))
+                    .map(|label| label.trim().to_string())
+                    .unwrap_or_else(|_| stem.clone());
+                inputs.push(Input {
+                    label: format!("{} {label}", chip.trim()).trim().to_string(),
+                    value,
+                    dir: dir.clone(),
+                    stem,
+                });
+            }
+        }
+        inputs
+    }
+
+    fn read_number(path: &Path) -> Option<f64> {
+        std::fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+}
@@ -0,0 +1,110 @@
+use std::{collections::HashMap, time::Instant};
+
+use sysinfo::{Pid, System};
+
+/// The processes at the moment the user took a snapshot, to compare later states against.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub taken: Instant,
+    processes: HashMap<Pid, ProcessSnapshot>,
+    total_memory: u64,
+}
+
+#[derive(Debug, Clone)]
+struct ProcessSnapshot {
+    name: String,
+    /// Distinguishes a reused PID from the process that had it before.
+    start_time: u64,
+    cpu: f32,
+    memory: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Appeared,
+    Disappeared,
+    Changed,
+}
+
+/// How a process differs from the snapshot.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub pid: Pid,
+    pub name: String,
+    pub kind: ChangeKind,
+    /// CPU usage now minus CPU usage at the snapshot, in percent.
+    pub cpu: f32,
+    /// Resident memory now minus resident memory at the snapshot, in bytes.
+    pub memory: i64,
+    /// Size of the change, used to put the biggest changes first.
+    score: f64,
+}
+
+impl Snapshot {
+    pub fn capture(system: &System) -> Self {
+        Self {
+            taken: Instant::now(),
+            processes: system
+                .processes()
+                .iter()
+                .map(|(&pid, process)| {
+                    let snapshot = ProcessSnapshot {
+                        name: process.name().to_string_lossy().to_string(),
+                        start_time: process.start_time(),
+                        cpu: process.cpu_usage(),
+                        memory: process.memory(),
+                    };
+                    (pid, snapshot)
+                })
+                .collect(),
+            total_memory: system.total_memory(),
+        }
+    }
+
+    /// Changes from the snapshot to the processes of `system`, biggest first. Processes whose
+    /// CPU and memory usage did not change are left out.
+    pub fn diff(&self, system: &System) -> Vec<Change> {
+        // CPU usage and memory share of the machine, both in percent, make changes comparable.
+        let memory_percent = |bytes: i64| bytes as f64 * 100.0 / self.total_memory.max(1) as f64;
+        let change = |pid, name: String, kind, cpu: f32, memory: i64| Change {
+            pid,
+            name,
+            kind,
+            cpu,
+            memory,
+            score: cpu.abs() as f64 + memory_percent(memory).abs(),
+        };
+
+        let mut changes = vec![];
+        for (&pid, process) in system.processes() {
+            let cpu = process.cpu_usage();
+            let memory = process.memory() as i64;
+            let name = process.name().to_string_lossy().to_string();
+            match self.processes.get(&pid) {
+                Some(before) if before.start_time == process.start_time() => {
+                    let (cpu, memory) = (cpu - before.cpu, memory - before.memory as i64);
+                    if cpu != 0.0 || memory != 0 {
+                        changes.push(change(pid, name, ChangeKind::Changed, cpu, memory));
+                    }
+                }
+                _ => changes.push(change(pid, name, ChangeKind::Appeared, cpu, memory)),
+            }
+        }
+        for (&pid, before) in &self.processes {
+            let gone = system
+                .process(pid)
+                .is_none_or(|process| process.start_time() != before.start_time);
+            if gone {
+                changes.push(change(
+                    pid,
+                    before.name.clone(),
+                    ChangeKind::Disappeared,
+                    -before.cpu,
+                    -(before.memory as i64),
+                ));
+            }
+        }
+        changes.sort_by(|a, b| b.score.total_cmp(&a.score));
+        changes
+    }
+}
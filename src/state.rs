@@ -0,0 +1,275 @@
+use std::{collections::HashMap, fmt::Write, path::PathBuf};
+
+use color_eyre::Result;
+
+use crate::{
+    columns::Column,
+    config::{parse_ini, state_dir},
+    layout::LayoutMode,
+};
+
+/// Number of search queries kept in the history file.
+pub const MAX_SEARCH_HISTORY: usize = 100;
+
+/// UI state that survives between sessions, stored in `$XDG_STATE_HOME/ratatop/state`.
+/// Search queries are kept one per line in `$XDG_STATE_HOME/ratatop/history`.
+#[derive(Debug, Clone)]
+pub struct State {
+    /// Widths of the columns the user resized.
+    pub column_widths: HashMap<Column, u16>,
+    /// Previous search queries, oldest first.
+    pub search_history: Vec<String>,
+    /// Visible columns of the process table, in display order.
+    pub columns: Vec<Column>,
+    pub sort_column: Column,
+    pub sort_descending: bool,
+    /// Show processes as a parent/child tree instead of a flat list.
+    pub tree: bool,
+    /// The search query in effect.
+    pub filter: String,
+    pub layout: LayoutMode,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            column_widths: HashMap::new(),
+            search_history: vec![],
+            columns: Column::DEFAULT.to_vec(),
+            sort_column: Column::default(),
+            sort_descending: true,
+            tree: false,
+            filter: String::new(),
+            layout: LayoutMode::default(),
+        }
+    }
+}
+
+impl State {
+    /// Load the saved state. The state is disposable, so a missing or broken file simply
+    /// yields the default state.
+    pub fn load() -> Self {
+        let mut state = path("state")
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default();
+        state.search_history = path("history")
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|text| {
+                text.lines()
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        state
+    }
+
+    fn parse(text: &str) -> Self {
+        let mut state = Self::default();
+        for entry in parse_ini(text).unwrap_or_default() {
+            match (entry.section.as_str(), entry.key.as_str()) {
+                ("widths", key) => {
+                    if let (Some(column), Ok(width)) = (Column::from_key(key), entry.value.parse())
+                    {
+                        state.column_widths.insert(column, width);
+                    }
+                }
+                ("view", "columns") => {
+                    let columns: Vec<_> = entry
+                        .value
+                        .split(',')
+                        .filter_map(|key| Column::from_key(key.trim()))
+                        .collect();
+                    if !columns.is_empty() {
+                        state.columns = columns;
+                    }
+                }
+                ("view", "sort") => {
+                    if let Some(column) = Column::from_key(&entry.value) {
+                        state.sort_column = column;
+                    }
+                }
+                ("view", "descending") => state.sort_descending = entry.value == "true",
+                ("view", "tree") => state.tree = entry.value == "true",
+                ("view", "filter") => state.filter = unescape(&entry.value),
+                ("view", "layout") => {
+                    if let Some(layout) = LayoutMode::from_key(&entry.value) {
+                        state.layout = layout;
+                    }
+                }
+                _ => {}
+            }
+        }
+        state
+    }
+
+    /// This saved state with what a session started with a profile changed outside the view:
+    /// the column widths and the search history.
+    pub fn with_session_of(&self, session: State) -> State {
+        State {
+            column_widths: session.column_widths,
+            search_history: session.search_history,
+            ..self.clone()
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let (Some(state_path), Some(history_path)) = (path("state"), path("history")) else {
+            return Ok(());
+        };
+        if let Some(dir) = state_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(state_path, self.to_text()?)?;
+
+        let start = self.search_history.len().saturating_sub(MAX_SEARCH_HISTORY);
+        let mut history = self.search_history[start..].join("\n");
+        history.push('\n');
+        std::fs::write(history_path, history)?;
+        Ok(())
+    }
+
+    /// The contents of the state file.
+    fn to_text(&self) -> Result<String> {
+        let mut text = String::from("[view]\n");
+        let columns: Vec<_> = self.columns.iter().map(|column| column.key()).collect();
+        writeln!(text, "columns = {}", columns.join(","))?;
+        writeln!(text, "sort = {}", self.sort_column.key())?;
+        writeln!(text, "descending = {}", self.sort_descending)?;
+        writeln!(text, "tree = {}", self.tree)?;
+        writeln!(text, "filter = \"{}\"", escape(&self.filter))?;
+        writeln!(text, "layout = {}", self.layout.key())?;
+        text.push_str("\n[widths]\n");
+        for column in Column::all() {
+            if let Some(width) = self.column_widths.get(&column) {
+                writeln!(text, "{} = {width}", column.key())?;
+            }
+        }
+        Ok(text)
+    }
+}
+
+/// Escape backslashes, quotes and line breaks, so any filter fits in a quoted value on one
+/// line.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Undo [`escape`]. Unknown escapes are kept as they are.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(c @ ('\\' | '"')) => unescaped.push(c),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+fn path(file: &str) -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("ratatop").join(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Profile;
+
+    #[test]
+    fn profile_view_is_not_saved() {
+        let saved = State {
+            columns: vec![Column::Pid, Column::Name],
+            filter: "firefox".to_string(),
+            ..State::default()
+        };
+        let profile = Profile {
+            columns: Some(vec![Column::Name, Column::Cpu]),
+            tree: Some(true),
+            filter: Some("cpu > 5".to_string()),
+            ..Profile::default()
+        };
+        let mut session = saved.clone();
+        profile.apply(&mut session);
+        session.column_widths.insert(Column::Name, 30);
+        session.search_history.push("cpu > 5".to_string());
+
+        let state = saved.with_session_of(session);
+        assert_eq!(state.columns, saved.columns);
+        assert!(!state.tree);
+        assert_eq!(state.filter, "firefox");
+        assert_eq!(state.column_widths.get(&Column::Name), Some(&30));
+        assert_eq!(state.search_history, ["cpu > 5"]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut state = State {
+            columns: vec![Column::Name, Column::Pid],
+            sort_column: Column::Pid,
+            sort_descending: false,
+            tree: true,
+            layout: LayoutMode::Compact,
+            ..State::default()
+        };
+        state.column_widths.insert(Column::Name, 24);
+        for filter in [
+            "",
+            "firefox",
+            r#"name == "foo""#,
+            "name ~ 'a b' && cpu > 5",
+            "\"",
+            "back\\slash\\",
+            "\\n is not a line break",
+            "two\nlines\r\n",
+            " padded ",
+        ] {
+            state.filter = filter.to_string();
+            let text = state.to_text().unwrap();
+            assert_eq!(
+                text.lines()
+                    .filter(|line| line.starts_with("filter"))
+                    .count(),
+                1
+            );
+            let loaded = State::parse(&text);
+            assert_eq!(loaded.filter, filter);
+            assert_eq!(loaded.columns, state.columns);
+            assert_eq!(loaded.sort_column, state.sort_column);
+            assert_eq!(loaded.sort_descending, state.sort_descending);
+            assert_eq!(loaded.tree, state.tree);
+            assert_eq!(loaded.layout, state.layout);
+            assert_eq!(loaded.column_widths, state.column_widths);
+        }
+    }
+
+    #[test]
+    fn unescaped_filters_still_load() {
+        let state = State::parse("[view]\nfilter = \"cpu > 5\"\n");
+        assert_eq!(state.filter, "cpu > 5");
+        let state = State::parse("[view]\nfilter = \"C:\\temp\"\n");
+        assert_eq!(state.filter, "C:\\temp");
+    }
+}
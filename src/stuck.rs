@@ -0,0 +1,58 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use sysinfo::{Pid, ProcessStatus, System};
+
+/// Notices processes that stay in uninterruptible sleep (D state), which usually means
+/// storage or NFS trouble.
+#[derive(Debug, Default)]
+pub struct StuckProcesses {
+    /// How long a process may stay in D state before it is reported, `alerts.uninterruptible`
+    /// in the config. `None` disables reporting.
+    threshold: Option<Duration>,
+    /// When each process entered D state, and whether it was reported already.
+    since: HashMap<Pid, (Instant, bool)>,
+}
+
+impl StuckProcesses {
+    pub fn new(threshold: Option<Duration>) -> Self {
+        Self {
+            threshold,
+            since: HashMap::new(),
+        }
+    }
+
+    pub fn threshold(&self) -> Option<Duration> {
+        self.threshold
+    }
+
+    /// Processes that just passed the threshold, each reported once per stretch in D state.
+    pub fn check(&mut self, system: &System) -> Vec<Pid> {
+        let Some(threshold) = self.threshold else {
+            return vec![];
+        };
+        let now = Instant::now();
+        self.since
+            .retain(|pid, _| system.process(*pid).is_some_and(is_stuck));
+        let mut overdue = vec![];
+        for (&pid, process) in system.processes() {
+            if !is_stuck(process) {
+                continue;
+            }
+            let (since, reported) = self.since.entry(pid).or_insert((now, false));
+            if !*reported && now.duration_since(*since) >= threshold {
+                *reported = true;
+                overdue.push(pid);
+            }
+        }
+        overdue.sort();
+        overdue
+    }
+}
+
+/// Is the process in uninterruptible sleep?
+pub fn is_stuck(process: &sysinfo::Process) -> bool {
+    process.status() == ProcessStatus::UninterruptibleDiskSleep
+}
@@ -0,0 +1,40 @@
+use sysinfo::{ProcessStatus, System};
+
+/// Process counts and the resource usage of ratatop itself, collected on every process
+/// refresh.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Summary {
+    pub total: usize,
+    pub running: usize,
+    pub sleeping: usize,
+    pub zombie: usize,
+    /// CPU usage of ratatop in percent.
+    pub own_cpu: f32,
+    /// Resident memory of ratatop in bytes.
+    pub own_memory: u64,
+}
+
+impl Summary {
+    pub fn collect(system: &System) -> Self {
+        let mut summary = Self {
+            total: system.processes().len(),
+            ..Self::default()
+        };
+        for process in system.processes().values() {
+            match process.status() {
+                ProcessStatus::Run => summary.running += 1,
+                ProcessStatus::Sleep | ProcessStatus::Idle => summary.sleeping += 1,
+                ProcessStatus::Zombie => summary.zombie += 1,
+                _ => {}
+            }
+        }
+        if let Some(own) = sysinfo::get_current_pid()
+            .ok()
+            .and_then(|pid| system.process(pid))
+        {
+            summary.own_cpu = own.cpu_usage();
+            summary.own_memory = own.memory();
+        }
+        summary
+    }
+}
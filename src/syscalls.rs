@@ -0,0 +1,411 @@
+//! A strace-like view of the system calls of a process, built with the `syscalls` feature on
+//! Linux.
+//!
+//! Every thread of the process is attached with `ptrace` by a tracer thread of its own, which
+//! stops it at each system call entry and exit. That slows the process down noticeably, so
+//! tracing only runs while the view is open.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    os::unix::thread::JoinHandleExt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Once,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::Stylize,
+    text::Line,
+    widgets::{Clear, Paragraph, Row, Table},
+    Frame,
+};
+use sysinfo::Pid;
+
+use crate::{i18n::Messages, layout, theme::Theme};
+
+#[cfg(target_arch = "x86_64")]
+mod names;
+
+/// Number of recent calls kept.
+const RECENT: usize = 200;
+/// Threads beyond this are not traced.
+const MAX_THREADS: usize = 64;
+/// Signal used to interrupt a tracer thread waiting for its tracee.
+const WAKE_SIGNAL: libc::c_int = libc::SIGUSR1;
+/// `PTRACE_EVENT_STOP` from `linux/ptrace.h`.
+const PTRACE_EVENT_STOP: libc::c_int = 128;
+
+/// A system call seen at its entry.
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub tid: i32,
+    pub number: u64,
+    /// The first three arguments.
+    pub args: [u64; 3],
+    /// Return value, `None` while the call is still running.
+    pub result: Option<i64>,
+}
+
+impl Call {
+    /// Like `read(3, 0x7ffd5e2c, 4096) = 12`.
+    pub fn format(&self) -> String {
+        let args: Vec<String> = self
+            .args
+            .iter()
+            .map(|&arg| {
+                if arg > 0xffff {
+                    format!("{arg:#x}")
+                } else {
+                    arg.to_string()
+                }
+            })
+            .collect();
+        let result = self
+            .result
+            .map_or_else(|| "?".to_string(), |result| result.to_string());
+        format!("{}({}) = {result}", name(self.number), args.join(", "))
+    }
+}
+
+/// What the tracer threads collected so far.
+#[derive(Debug, Default)]
+pub struct Trace {
+    /// Number of calls of each system call.
+    pub counts: HashMap<u64, u64>,
+    /// The latest calls, oldest first.
+    pub recent: VecDeque<Call>,
+    /// Why a thread could not be traced, e.g. missing permissions.
+    pub error: Option<String>,
+}
+
+/// Traces a process until dropped.
+#[derive(Debug)]
+pub struct Tracer {
+    trace: Arc<Mutex<Trace>>,
+    stop: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl Tracer {
+    pub fn attach(pid: Pid) -> io::Result<Self> {
+        static HANDLER: Once = Once::new();
+        HANDLER.call_once(|| {
+            extern "C" fn wake(_: libc::c_int) {}
+            // SAFETY: the handler does nothing. Without SA_RESTART the signal interrupts
+            // `waitpid` in the tracer thread it is sent to.
+            unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = wake as extern "C" fn(libc::c_int) as libc::sighandler_t;
+                libc::sigaction(WAKE_SIGNAL, &action, std::ptr::null_mut());
+            }
+        });
+
+        let mut tids: Vec<i32> = std::fs::read_dir(format!("/proc/{pid}/task"))?
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+            .collect();
+        tids.sort();
+        tids.truncate(MAX_THREADS);
+        let trace = Arc::new(Mutex::new(Trace::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let threads = tids
+            .into_iter()
+            .map(|tid| {
+                let (trace, stop) = (Arc::clone(&trace), Arc::clone(&stop));
+                std::thread::spawn(move || {
+                    if let Err(err) = run(tid, &stop, &trace) {
+                        let mut trace = trace.lock().unwrap();
+                        trace.error.get_or_insert_with(|| err.to_string());
+                    }
+                })
+            })
+            .collect();
+        Ok(Self {
+            trace,
+            stop,
+            threads,
+        })
+    }
+
+    pub fn trace(&self) -> std::sync::MutexGuard<'_, Trace> {
+        self.trace.lock().unwrap()
+    }
+}
+
+impl Drop for Tracer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for thread in self.threads.drain(..) {
+            // The signal may arrive just before the thread starts waiting, so repeat it.
+            while !thread.is_finished() {
+                // SAFETY: the thread has not been joined, so its handle is valid.
+                unsafe { libc::pthread_kill(thread.as_pthread_t(), WAKE_SIGNAL) };
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The system calls view of the process details: call counts and rates next to the latest
+/// calls.
+#[derive(Debug)]
+pub struct SyscallView {
+    pid: Pid,
+    tracer: Tracer,
+    /// Counts at the last rate update.
+    previous: HashMap<u64, u64>,
+    /// Calls per second since the previous update, by system call.
+    rates: HashMap<u64, u64>,
+    last_update: Instant,
+}
+
+impl SyscallView {
+    pub fn open(pid: Pid) -> io::Result<Self> {
+        Ok(Self {
+            pid,
+            tracer: Tracer::attach(pid)?,
+            previous: HashMap::new(),
+            rates: HashMap::new(),
+            last_update: Instant::now(),
+        })
+    }
+
+    pub fn render(&mut self, frame: &mut Frame<'_>, messages: &Messages, theme: &Theme) {
+        let trace = self.tracer.trace();
+        let elapsed = self.last_update.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.rates = trace
+                .counts
+                .iter()
+                .map(|(&number, &count)| {
+                    let previous = self.previous.get(&number).copied().unwrap_or(0);
+                    let rate = (count - previous) as f64 / elapsed.as_secs_f64();
+                    (number, rate.round() as u64)
+                })
+                .collect();
+            self.previous = trace.counts.clone();
+            self.last_update = Instant::now();
+        }
+
+        let area = layout::centered(
+            frame.area(),
+            frame.area().width * 9 / 10,
+            frame.area().height * 4 / 5,
+        );
+        frame.render_widget(Clear, area);
+        let block = theme
+            .block()
+            .title(format!("{} {}", messages.syscalls_title, self.pid))
+            .title_bottom(messages.syscalls_hint);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let [counts_area, recent_area] =
+            Layout::horizontal([Constraint::Length(40), Constraint::Fill(1)])
+                .spacing(2)
+                .areas(inner);
+
+        let mut counts: Vec<(u64, u64, u64)> = trace
+            .counts
+            .iter()
+            .map(|(&number, &count)| {
+                let rate = self.rates.get(&number).copied().unwrap_or(0);
+                (number, rate, count)
+            })
+            .collect();
+        counts.sort_by_key(|&(_, rate, count)| std::cmp::Reverse((rate, count)));
+        let rows = counts.into_iter().map(|(number, rate, count)| {
+            Row::new([
+                Line::from(name(number)),
+                Line::from(rate.to_string()).right_aligned(),
+                Line::from(count.to_string()).right_aligned(),
+            ])
+        });
+        let header = Row::new([
+            Line::from(messages.column_name),
+            Line::from(messages.label_per_second).right_aligned(),
+            Line::from(messages.label_calls).right_aligned(),
+        ])
+        .bold();
+        frame.render_widget(
+            Table::new(
+                rows,
+                [
+                    Constraint::Fill(1),
+                    Constraint::Length(9),
+                    Constraint::Length(10),
+                ],
+            )
+            .header(header),
+            counts_area,
+        );
+
+        let mut lines: Vec<Line> = vec![Line::from(messages.label_recent).bold()];
+        if let Some(error) = &trace.error {
+            lines.push(Line::from(error.as_str()).fg(theme.alert));
+        }
+        let room = (recent_area.height as usize).saturating_sub(lines.len());
+        let skip = trace.recent.len().saturating_sub(room);
+        lines.extend(
+            trace
+                .recent
+                .iter()
+                .skip(skip)
+                .map(|call| Line::from(format!("{:>7} {}", call.tid, call.format()))),
+        );
+        frame.render_widget(Paragraph::new(lines), recent_area);
+    }
+}
+
+/// Name of a system call, or its number on architectures without a table.
+pub fn name(number: u64) -> String {
+    #[cfg(target_arch = "x86_64")]
+    if let Ok(index) = names::NAMES.binary_search_by_key(&number, |&(number, _)| number) {
+        return names::NAMES[index].1.to_string();
+    }
+    format!("syscall_{number}")
+}
+
+/// Trace one thread until `stop` is set or the thread exits.
+fn run(tid: i32, stop: &AtomicBool, trace: &Mutex<Trace>) -> io::Result<()> {
+    ptrace(
+        libc::PTRACE_SEIZE.into(),
+        tid,
+        0,
+        libc::PTRACE_O_TRACESYSGOOD as usize,
+    )?;
+    ptrace(libc::PTRACE_INTERRUPT.into(), tid, 0, 0)?;
+    loop {
+        let status = match wait(tid) {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {
+                if stop.load(Ordering::Relaxed) {
+                    return detach(tid);
+                }
+                continue;
+            }
+            status => status?,
+        };
+        if !libc::WIFSTOPPED(status) {
+            // The thread exited.
+            return Ok(());
+        }
+        let signal = libc::WSTOPSIG(status);
+        let event = status >> 16;
+        // Signals stopped at are delivered when resuming.
+        let pending = if event == 0 && signal != libc::SIGTRAP | 0x80 {
+            signal
+        } else {
+            0
+        };
+        if stop.load(Ordering::Relaxed) {
+            return ptrace(libc::PTRACE_DETACH.into(), tid, 0, pending as usize);
+        }
+        if signal == libc::SIGTRAP | 0x80 {
+            record(tid, trace)?;
+        }
+        let job_control = matches!(
+            signal,
+            libc::SIGSTOP | libc::SIGTSTP | libc::SIGTTIN | libc::SIGTTOU
+        );
+        if event == PTRACE_EVENT_STOP && job_control {
+            // Keep the thread stopped like an untraced one would be.
+            ptrace(libc::PTRACE_LISTEN.into(), tid, 0, 0)?;
+        } else {
+            ptrace(libc::PTRACE_SYSCALL.into(), tid, 0, pending as usize)?;
+        }
+    }
+}
+
+/// Stop a running thread and detach from it.
+fn detach(tid: i32) -> io::Result<()> {
+    ptrace(libc::PTRACE_INTERRUPT.into(), tid, 0, 0)?;
+    loop {
+        match wait(tid) {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+            Ok(status) if !libc::WIFSTOPPED(status) => return Ok(()),
+            Ok(status) => {
+                let signal = libc::WSTOPSIG(status);
+                let pending = if status >> 16 == 0 && signal != libc::SIGTRAP | 0x80 {
+                    signal
+                } else {
+                    0
+                };
+                return ptrace(libc::PTRACE_DETACH.into(), tid, 0, pending as usize);
+            }
+        }
+    }
+}
+
+/// Add the call the thread stopped at to `trace`.
+fn record(tid: i32, trace: &Mutex<Trace>) -> io::Result<()> {
+    // `struct ptrace_syscall_info`: op, padding and arch, instruction and stack pointer, then
+    // the number and arguments at entry or the return value at exit.
+    let mut info = [0u64; 11];
+    ptrace(
+        libc::PTRACE_GET_SYSCALL_INFO.into(),
+        tid,
+        std::mem::size_of_val(&info),
+        info.as_mut_ptr() as usize,
+    )?;
+    let mut trace = trace.lock().unwrap();
+    match info[0] as u8 {
+        // Entry.
+        1 => {
+            *trace.counts.entry(info[3]).or_default() += 1;
+            if trace.recent.len() == RECENT {
+                trace.recent.pop_front();
+            }
+            trace.recent.push_back(Call {
+                tid,
+                number: info[3],
+                args: [info[4], info[5], info[6]],
+                result: None,
+            });
+        }
+        // Exit.
+        2 => {
+            if let Some(call) = trace
+                .recent
+                .iter_mut()
+                .rev()
+                .find(|call| call.tid == tid && call.result.is_none())
+            {
+                call.result = Some(info[3] as i64);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn ptrace(request: i64, tid: i32, addr: usize, data: usize) -> io::Result<()> {
+    // SAFETY: the requests used here only write to memory passed in `data` with its size in
+    // `addr`.
+    let result = unsafe {
+        libc::ptrace(
+            request as _,
+            tid,
+            addr as *mut libc::c_void,
+            data as *mut libc::c_void,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn wait(tid: i32) -> io::Result<libc::c_int> {
+    let mut status = 0;
+    // SAFETY: `status` is a valid pointer.
+    if unsafe { libc::waitpid(tid, &mut status, libc::__WALL) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(status)
+}
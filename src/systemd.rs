@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use sysinfo::{Pid, System};
+
+/// Resource usage of the processes of one systemd unit.
+#[derive(Debug, Clone)]
+pub struct UnitSummary {
+    pub name: String,
+    pub processes: usize,
+    /// Summed CPU usage in percent.
+    pub cpu: f32,
+    /// Summed resident memory in bytes.
+    pub memory: u64,
+}
+
+/// The systemd unit of a process, from the innermost unit of its cgroup path.
+#[cfg(target_os = "linux")]
+pub fn unit_of(pid: Pid) -> Option<String> {
+    let cgroup = std::fs::read_to_string(format!("/proc/{pid}/cgroup")).ok()?;
+    parse_cgroup(&cgroup)
+}
+
+/// systemd only exists on Linux.
+#[cfg(not(target_os = "linux"))]
+pub fn unit_of(_pid: Pid) -> Option<String> {
+    None
+}
+
+/// Find the unit in `/proc/<pid>/cgroup`: the unified hierarchy (`0::/system.slice/x.service`)
+/// or the systemd controller of cgroup v1 (`1:name=systemd:/...`).
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cgroup(cgroup: &str) -> Option<String> {
+    let path = cgroup.lines().find_map(|line| {
+        let mut fields = line.splitn(3, ':');
+        let (_, controllers, path) = (fields.next()?, fields.next()?, fields.next()?);
+        (controllers.is_empty() || controllers == "name=systemd").then_some(path)
+    })?;
+    path.rsplit('/')
+        .find(|component| {
+            [".service", ".scope", ".slice", ".socket", ".mount"]
+                .iter()
+                .any(|suffix| component.ends_with(suffix))
+        })
+        .map(str::to_string)
+}
+
+/// The unit of every process that belongs to one.
+#[cfg(not(windows))]
+pub fn units(system: &System) -> HashMap<Pid, String> {
+    system
+        .processes()
+        .keys()
+        .filter_map(|&pid| unit_of(pid).map(|unit| (pid, unit)))
+        .collect()
+}
+
+/// On Windows the Win32 services take the place of the units.
+#[cfg(windows)]
+pub fn units(system: &System) -> HashMap<Pid, String> {
+    let mut services = crate::windows::services();
+    services.retain(|pid, _| system.process(*pid).is_some());
+    services
+}
+
+/// Aggregate the processes of `system` per unit, busiest unit first.
+pub fn summarize(system: &System, units: &HashMap<Pid, String>) -> Vec<UnitSummary> {
+    let mut summaries: HashMap<&str, UnitSummary> = HashMap::new();
+    for (pid, unit) in units {
+        let Some(process) = system.process(*pid) else {
+            continue;
+        };
+        let summary = summaries.entry(unit).or_insert_with(|| UnitSummary {
+            name: unit.clone(),
+            processes: 0,
+            cpu: 0.0,
+            memory: 0,
+        });
+        summary.processes += 1;
+        summary.cpu += process.cpu_usage();
+        summary.memory += process.memory();
+    }
+    let mut summaries: Vec<_> = summaries.into_values().collect();
+    summaries.sort_by(|a, b| b.cpu.total_cmp(&a.cpu).then_with(|| a.name.cmp(&b.name)));
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_hierarchy() {
+        assert_eq!(
+            parse_cgroup("0::/system.slice/foo.service\n").as_deref(),
+            Some("foo.service")
+        );
+        assert_eq!(
+            parse_cgroup("0::/system.slice/docker.service/init\n").as_deref(),
+            Some("docker.service")
+        );
+        assert_eq!(
+            parse_cgroup("0::/init.scope\n").as_deref(),
+            Some("init.scope")
+        );
+        assert_eq!(parse_cgroup("0::/\n").as_deref(), None);
+    }
+
+    #[test]
+    fn user_slices() {
+        assert_eq!(
+            parse_cgroup("0::/user.slice/user-1000.slice/session-2.scope\n").as_deref(),
+            Some("session-2.scope")
+        );
+        let manager = "0::/user.slice/user-1000.slice/user@1000.service";
+        assert_eq!(
+            parse_cgroup(&format!("{manager}/app.slice/app-firefox-1234.scope\n")).as_deref(),
+            Some("app-firefox-1234.scope")
+        );
+        assert_eq!(
+            parse_cgroup(&format!("{manager}/session.slice/pipewire.service\n")).as_deref(),
+            Some("pipewire.service")
+        );
+    }
+
+    #[test]
+    fn legacy_hierarchy() {
+        let cgroup = "\
+12:pids:/system.slice/sshd.service
+11:memory:/system.slice/sshd.service
+5:cpu,cpuacct:/
+1:name=systemd:/system.slice/sshd.service
+";
+        assert_eq!(parse_cgroup(cgroup).as_deref(), Some("sshd.service"));
+        let hybrid = "\
+4:devices:/user.slice
+1:name=systemd:/user.slice/user-1000.slice/session-3.scope
+0::/user.slice/user-1000.slice/session-3.scope
+";
+        assert_eq!(parse_cgroup(hybrid).as_deref(), Some("session-3.scope"));
+        assert_eq!(
+            parse_cgroup("12:pids:/system.slice/sshd.service\n").as_deref(),
+            None
+        );
+    }
+}
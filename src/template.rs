@@ -0,0 +1,371 @@
+//! Format strings of custom columns, like `{memory_mb}/{virtual_mb}` or `{cpu:5.1}%`.
+//!
+//! A placeholder names a process field and optionally a format spec of alignment (`<`, `>`
+//! or `^`), width and precision. `{{` and `}}` stand for literal braces.
+
+use std::cmp::Ordering;
+
+use crate::{columns::ProcessInfo, units};
+
+/// A parsed format string.
+#[derive(Debug, Clone)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Placeholder(Field, Spec),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Spec {
+    align: Option<char>,
+    width: usize,
+    precision: Option<usize>,
+}
+
+/// Process fields templates can refer to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Pid,
+    Ppid,
+    Name,
+    Exe,
+    Cmd,
+    Status,
+    Cpu,
+    Memory,
+    MemoryKb,
+    MemoryMb,
+    Virtual,
+    VirtualKb,
+    VirtualMb,
+    RunTime,
+    DiskRead,
+    DiskWritten,
+    Threads,
+    Swap,
+}
+
+/// The value of a field for one process.
+enum Value {
+    Text(String),
+    /// A number and the precision it is shown with unless the spec says otherwise.
+    Number(f64, usize),
+    /// Shown with a binary unit unless the spec gives a precision.
+    Bytes(u64),
+    /// Not known for this process, e.g. procfs details on other platforms.
+    Missing,
+}
+
+impl Template {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => return Err(format!("unclosed `{{{placeholder}`")),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    let (name, spec) = placeholder
+                        .split_once(':')
+                        .unwrap_or((placeholder.as_str(), ""));
+                    let field = Field::from_name(name.trim())
+                        .ok_or_else(|| format!("unknown field `{}`", name.trim()))?;
+                    let spec =
+                        Spec::parse(spec).ok_or_else(|| format!("invalid format `{spec}`"))?;
+                    parts.push(Part::Placeholder(field, spec));
+                }
+                '}' => return Err("unmatched `}`".to_string()),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    /// Does the template show details from [`crate::procfs::ProcStatus`]?
+    pub fn needs_status(&self) -> bool {
+        self.fields()
+            .any(|field| matches!(field, Field::Threads | Field::Swap))
+    }
+
+    pub fn render(&self, info: ProcessInfo) -> String {
+        let mut text = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => text.push_str(literal),
+                Part::Placeholder(field, spec) => text.push_str(&spec.apply(field.value(info))),
+            }
+        }
+        text
+    }
+
+    /// Ascending order of two processes: by the first numeric field if there is one, by the
+    /// rendered text otherwise.
+    pub fn compare(&self, a: ProcessInfo, b: ProcessInfo) -> Ordering {
+        let Some(field) = self.fields().find(|field| field.is_numeric()) else {
+            return self
+                .render(a)
+                .to_lowercase()
+                .cmp(&self.render(b).to_lowercase());
+        };
+        match (field.value(a).number(), field.value(b).number()) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        }
+    }
+
+    fn fields(&self) -> impl Iterator<Item = Field> + '_ {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Placeholder(field, _) => Some(*field),
+            Part::Literal(_) => None,
+        })
+    }
+}
+
+impl Spec {
+    /// Parse the part after the colon, like `>8.2`.
+    fn parse(text: &str) -> Option<Self> {
+        let mut spec = Spec::default();
+        let mut rest = text.trim();
+        if let Some(align) = rest.chars().next().filter(|c| matches!(c, '<' | '>' | '^')) {
+            spec.align = Some(align);
+            rest = &rest[1..];
+        }
+        let (width, precision) = match rest.split_once('.') {
+            Some((width, precision)) => (width, Some(precision.parse().ok()?)),
+            None => (rest, None),
+        };
+        if !width.is_empty() {
+            spec.width = width.parse().ok()?;
+        }
+        spec.precision = precision;
+        Some(spec)
+    }
+
+    fn apply(self, value: Value) -> String {
+        let text = match (value, self.precision) {
+            (Value::Text(text), Some(precision)) => text.chars().take(precision).collect(),
+            (Value::Text(text), None) => text,
+            (Value::Number(number, default), precision) => {
+                format!("{number:.*}", precision.unwrap_or(default))
+            }
+            (Value::Bytes(bytes), Some(precision)) => format!("{:.*}", precision, bytes as f64),
+            (Value::Bytes(bytes), None) => units::bytes(bytes),
+            (Value::Missing, _) => "-".to_string(),
+        };
+        let width = self.width;
+        // Numbers line up on the right unless told otherwise, like in Rust format strings.
+        match self.align {
+            Some('<') => format!("{text:<width$}"),
+            Some('^') => format!("{text:^width$}"),
+            Some(_) => format!("{text:>width$}"),
+            None if text.parse::<f64>().is_ok() => format!("{text:>width$}"),
+            None => format!("{text:<width$}"),
+        }
+    }
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "pid" => Field::Pid,
+            "ppid" => Field::Ppid,
+            "name" => Field::Name,
+            "exe" => Field::Exe,
+            "cmd" => Field::Cmd,
+            "status" => Field::Status,
+            "cpu" => Field::Cpu,
+            "memory" => Field::Memory,
+            "memory_kb" => Field::MemoryKb,
+            "memory_mb" => Field::MemoryMb,
+            "virtual" => Field::Virtual,
+            "virtual_kb" => Field::VirtualKb,
+            "virtual_mb" => Field::VirtualMb,
+            "run_time" => Field::RunTime,
+            "disk_read" => Field::DiskRead,
+            "disk_written" => Field::DiskWritten,
+            "threads" => Field::Threads,
+            "swap" => Field::Swap,
+            _ => return None,
+        })
+    }
+
+    fn is_numeric(self) -> bool {
+        !matches!(self, Field::Name | Field::Exe | Field::Cmd | Field::Status)
+    }
+
+    fn value(self, info: ProcessInfo) -> Value {
+        let process = info.process;
+        let kb = |bytes: u64| Value::Number(bytes as f64 / 1024.0, 0);
+        let mb = |bytes: u64| Value::Number(bytes as f64 / (1024.0 * 1024.0), 1);
+        let status =
+            |field: fn(&crate::procfs::ProcStatus) -> Option<u64>| info.status.and_then(field);
+        match self {
+            Field::Pid => Value::Number(process.pid().as_u32() as f64, 0),
+            Field::Ppid => process
+                .parent()
+                .map_or(Value::Missing, |pid| Value::Number(pid.as_u32() as f64, 0)),
+            Field::Name => Value::Text(process.name().to_string_lossy().to_string()),
+            Field::Exe => process.exe().map_or(Value::Missing, |exe| {
+                Value::Text(exe.to_string_lossy().to_string())
+            }),
+            Field::Cmd => Value::Text(
+                process
+                    .cmd()
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            Field::Status => Value::Text(process.status().to_string()),
+            Field::Cpu => Value::Number(process.cpu_usage() as f64, 1),
+            Field::Memory => Value::Bytes(process.memory()),
+            Field::MemoryKb => kb(process.memory()),
+            Field::MemoryMb => mb(process.memory()),
+            Field::Virtual => Value::Bytes(process.virtual_memory()),
+            Field::VirtualKb => kb(process.virtual_memory()),
+            Field::VirtualMb => mb(process.virtual_memory()),
+            Field::RunTime => Value::Number(process.run_time() as f64, 0),
+            Field::DiskRead => Value::Bytes(process.disk_usage().total_read_bytes),
+            Field::DiskWritten => Value::Bytes(process.disk_usage().total_written_bytes),
+            Field::Threads => status(|status| status.threads)
+                .map_or(Value::Missing, |threads| Value::Number(threads as f64, 0)),
+            Field::Swap => status(|status| status.swap).map_or(Value::Missing, Value::Bytes),
+        }
+    }
+}
+
+impl Value {
+    fn number(&self) -> Option<f64> {
+        match self {
+            Value::Number(number, _) => Some(*number),
+            Value::Bytes(bytes) => Some(*bytes as f64),
+            Value::Text(_) | Value::Missing => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(text: &str) -> Vec<Part> {
+        Template::parse(text).unwrap().parts
+    }
+
+    fn literal(text: &str) -> Part {
+        Part::Literal(text.to_string())
+    }
+
+    fn spec(align: Option<char>, width: usize, precision: Option<usize>) -> Spec {
+        Spec {
+            align,
+            width,
+            precision,
+        }
+    }
+
+    #[test]
+    fn literal_text() {
+        assert_eq!(parts("no fields"), [literal("no fields")]);
+        assert_eq!(parts(""), []);
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(
+            parts("{memory_mb}/{ virtual_mb } MB"),
+            [
+                Part::Placeholder(Field::MemoryMb, Spec::default()),
+                literal("/"),
+                Part::Placeholder(Field::VirtualMb, Spec::default()),
+                literal(" MB"),
+            ]
+        );
+    }
+
+    #[test]
+    fn specs() {
+        assert_eq!(
+            parts("{cpu:5.1}%"),
+            [
+                Part::Placeholder(Field::Cpu, spec(None, 5, Some(1))),
+                literal("%")
+            ]
+        );
+        assert_eq!(
+            parts("{name:<12}{pid:>8}{status:^7}{memory:.0}"),
+            [
+                Part::Placeholder(Field::Name, spec(Some('<'), 12, None)),
+                Part::Placeholder(Field::Pid, spec(Some('>'), 8, None)),
+                Part::Placeholder(Field::Status, spec(Some('^'), 7, None)),
+                Part::Placeholder(Field::Memory, spec(None, 0, Some(0))),
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_braces() {
+        assert_eq!(
+            parts("{{{pid}}}"),
+            [
+                literal("{"),
+                Part::Placeholder(Field::Pid, Spec::default()),
+                literal("}")
+            ]
+        );
+        assert_eq!(parts("{{}}"), [literal("{}")]);
+    }
+
+    #[test]
+    fn errors() {
+        let err = |text| Template::parse(text).unwrap_err();
+        assert_eq!(err("{nope}"), "unknown field `nope`");
+        assert_eq!(err("{pid"), "unclosed `{pid`");
+        assert_eq!(err("pid}"), "unmatched `}`");
+        assert_eq!(err("{pid:x}"), "invalid format `x`");
+        assert_eq!(err("{cpu:5.}"), "invalid format `5.`");
+    }
+
+    #[test]
+    fn alignment() {
+        let number = || Value::Number(2.5, 1);
+        let text = || Value::Text("ab".to_string());
+        assert_eq!(spec(None, 6, None).apply(number()), "   2.5");
+        assert_eq!(spec(None, 6, Some(2)).apply(number()), "  2.50");
+        assert_eq!(spec(Some('<'), 6, None).apply(number()), "2.5   ");
+        assert_eq!(spec(None, 4, None).apply(text()), "ab  ");
+        assert_eq!(spec(Some('>'), 4, None).apply(text()), "  ab");
+        assert_eq!(spec(Some('^'), 4, None).apply(text()), " ab ");
+        assert_eq!(spec(None, 0, Some(1)).apply(text()), "a");
+        assert_eq!(spec(None, 3, None).apply(Value::Missing), "-  ");
+        assert_eq!(
+            spec(None, 0, None).apply(Value::Bytes(2048)),
+            units::bytes(2048)
+        );
+    }
+}
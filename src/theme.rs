@@ -0,0 +1,354 @@
+use std::cmp::Ordering;
+
+use ratatui::{
+    style::{Color, Style, Stylize},
+    symbols::border,
+    widgets::Block,
+};
+
+/// The background brightness of the terminal ratatop is running in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Background {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Background {
+    /// Detect the terminal background.
+    ///
+    /// Uses `COLORFGBG` when the terminal exports it and falls back to an OSC 11 query.
+    /// Must be called before the terminal is switched to the alternate screen.
+    pub fn detect() -> Self {
+        if let Some(background) = std::env::var("COLORFGBG")
+            .ok()
+            .and_then(|value| from_colorfgbg(&value))
+        {
+            return background;
+        }
+        query_osc11().unwrap_or_default()
+    }
+}
+
+/// Colors used throughout the user interface.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// Chart lines and axes, and pinned processes.
+    pub accent: Color,
+    /// Background of the selected table row.
+    pub selection: Color,
+    /// User, system, iowait and steal series of the CPU time breakdown.
+    pub cpu_breakdown: [Color; 4],
+    /// Notices, errors and processes that disappeared.
+    pub alert: Color,
+    /// Values past a warning threshold, short of [`Self::alert`].
+    pub warning: Color,
+    /// Processes that appeared since the snapshot.
+    pub added: Color,
+    /// Series of the charts of the processes given with `--pid`.
+    pub series: [Color; 6],
+    /// Convey everything as text, for monochrome terminals and screen readers: no colors,
+    /// the selection marked by reverse video next to `>>`, and numbers instead of charts.
+    pub plain: bool,
+    /// Draw borders, charts and symbols with ASCII characters only, for terminals that
+    /// mangle Unicode.
+    pub ascii: bool,
+}
+
+impl Theme {
+    pub fn new(background: Background) -> Self {
+        match background {
+            Background::Dark => Self {
+                accent: Color::Cyan,
+                selection: Color::DarkGray,
+                cpu_breakdown: [Color::Green, Color::Red, Color::Yellow, Color::Magenta],
+                alert: Color::Red,
+                warning: Color::Yellow,
+                added: Color::Green,
+                series: [
+                    Color::Cyan,
+                    Color::Green,
+                    Color::Yellow,
+                    Color::Magenta,
+                    Color::Red,
+                    Color::Blue,
+                ],
+                plain: false,
+                ascii: false,
+            },
+            Background::Light => Self {
+                accent: Color::Blue,
+                selection: Color::Gray,
+                cpu_breakdown: [
+                    Color::Green,
+                    Color::Red,
+                    Color::Rgb(176, 128, 0),
+                    Color::Magenta,
+                ],
+                alert: Color::Red,
+                warning: Color::Rgb(176, 128, 0),
+                added: Color::Green,
+                series: [
+                    Color::Blue,
+                    Color::Green,
+                    Color::Rgb(176, 128, 0),
+                    Color::Magenta,
+                    Color::Red,
+                    Color::Cyan,
+                ],
+                plain: false,
+                ascii: false,
+            },
+        }
+    }
+
+    /// The theme of `--no-color` and `NO_COLOR`, see [`Self::plain`].
+    pub fn plain() -> Self {
+        Self {
+            accent: Color::Reset,
+            selection: Color::Reset,
+            cpu_breakdown: [Color::Reset; 4],
+            alert: Color::Reset,
+            warning: Color::Reset,
+            added: Color::Reset,
+            series: [Color::Reset; 6],
+            plain: true,
+            ascii: false,
+        }
+    }
+
+    /// A bordered block.
+    pub fn block(&self) -> Block<'static> {
+        let block = Block::bordered();
+        if self.ascii {
+            block.border_set(ASCII_BORDER)
+        } else {
+            block
+        }
+    }
+
+    /// Marker of the sort column.
+    pub fn sort_marker(&self, descending: bool) -> char {
+        match (self.ascii, descending) {
+            (false, true) => '▼',
+            (false, false) => '▲',
+            (true, true) => 'v',
+            (true, false) => '^',
+        }
+    }
+
+    /// Unit of temperatures.
+    pub fn celsius(&self) -> &'static str {
+        if self.ascii {
+            "C"
+        } else {
+            "°C"
+        }
+    }
+
+    /// Marker of processes whose memory keeps growing.
+    pub fn growth_marker(&self) -> char {
+        if self.ascii {
+            '^'
+        } else {
+            '↑'
+        }
+    }
+
+    /// Arrow after the CPU usage of a process rising, falling or staying steady.
+    pub fn trend_marker(&self, direction: Ordering) -> char {
+        match (direction, self.ascii) {
+            (Ordering::Greater, false) => '↑',
+            (Ordering::Less, false) => '↓',
+            (Ordering::Equal, false) => '→',
+            (Ordering::Greater, true) => '^',
+            (Ordering::Less, true) => 'v',
+            (Ordering::Equal, true) => '-',
+        }
+    }
+
+    /// Marker of apps put to sleep by App Nap.
+    pub fn nap_marker(&self) -> char {
+        if self.ascii {
+            'z'
+        } else {
+            '☾'
+        }
+    }
+
+    /// Prefix of child processes in tree mode.
+    pub fn tree_branch(&self) -> &'static str {
+        if self.ascii {
+            "`- "
+        } else {
+            "└ "
+        }
+    }
+
+    /// Style of selected rows and items.
+    pub fn selection_style(&self) -> Style {
+        if self.plain {
+            Style::default().reversed()
+        } else {
+            Style::default().bg(self.selection)
+        }
+    }
+}
+
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(Background::default())
+    }
+}
+
+/// `COLORFGBG` looks like `15;0` or `0;default;15`, the last field being the background
+/// color index.
+fn from_colorfgbg(value: &str) -> Option<Background> {
+    let bg = value.rsplit(';').next()?.parse::<u8>().ok()?;
+    match bg {
+        7 | 9..=15 => Some(Background::Light),
+        _ => Some(Background::Dark),
+    }
+}
+
+/// Parse an OSC 11 reply such as `\x1b]11;rgb:ffff/ffff/ffff\x07`.
+fn from_osc11(reply: &str) -> Option<Background> {
+    let rgb = reply.split("rgb:").nth(1)?;
+    let rgb = rgb.trim_end_matches(['\x07', '\x1b', '\\']);
+    let mut channels = rgb.split('/').map(|channel| {
+        let max = 16f64.powi(channel.len() as i32) - 1.0;
+        u32::from_str_radix(channel, 16)
+            .ok()
+            .map(|value| value as f64 / max)
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    })
+}
+
+/// Where an OSC reply ends: at its BEL or ST terminator, whichever comes first.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn reply_end(reply: &[u8]) -> Option<usize> {
+    let bel = reply.iter().position(|&b| b == 0x07);
+    let st = reply.windows(2).position(|pair| pair == b"\x1b\\");
+    bel.into_iter().chain(st).min()
+}
+
+#[cfg(unix)]
+fn query_osc11() -> Option<Background> {
+    use std::{
+        io::Write,
+        time::{Duration, Instant},
+    };
+
+    /// How long to wait for the whole reply; terminals that do not support the query never
+    /// answer.
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    // Read the raw descriptor: std's buffered stdin would swallow the reply on the first read
+    // and leave nothing for poll to see.
+    let read_available = |timeout: Duration, buffer: &mut [u8]| -> Option<usize> {
+        let mut fds = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: poll and read only touch the given struct and buffer.
+        if unsafe { libc::poll(&mut fds, 1, timeout.as_millis() as libc::c_int) } <= 0 {
+            return None;
+        }
+        let read =
+            unsafe { libc::read(libc::STDIN_FILENO, buffer.as_mut_ptr().cast(), buffer.len()) };
+        usize::try_from(read).ok().filter(|&read| read > 0)
+    };
+
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let reply = (|| {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(b"\x1b]11;?\x07").ok()?;
+        stdout.flush().ok()?;
+
+        let deadline = Instant::now() + TIMEOUT;
+        let mut reply = Vec::new();
+        let mut buffer = [0u8; 64];
+        // The reply ends with BEL or ST; keys typed meanwhile may follow in the same read.
+        let end = loop {
+            if let Some(end) = reply_end(&reply) {
+                break end;
+            }
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            let read = read_available(remaining, &mut buffer)?;
+            reply.extend_from_slice(&buffer[..read]);
+        };
+        String::from_utf8(reply[..end].to_vec()).ok()
+    })();
+    // Whatever is left of a slow or partial reply must not reach the key handling as typed
+    // characters.
+    let mut buffer = [0u8; 64];
+    while read_available(Duration::ZERO, &mut buffer).is_some() {}
+    crossterm::terminal::disable_raw_mode().ok()?;
+    from_osc11(&reply?)
+}
+
+#[cfg(not(unix))]
+fn query_osc11() -> Option<Background> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colorfgbg() {
+        assert_eq!(from_colorfgbg("15;0"), Some(Background::Dark));
+        assert_eq!(from_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(from_colorfgbg("0;7"), Some(Background::Light));
+        assert_eq!(from_colorfgbg("15;default;0"), Some(Background::Dark));
+        assert_eq!(from_colorfgbg("default;default"), None);
+        assert_eq!(from_colorfgbg(""), None);
+    }
+
+    #[test]
+    fn osc11() {
+        for end in ["\x07", "\x1b\\", ""] {
+            assert_eq!(
+                from_osc11(&format!("\x1b]11;rgb:ffff/ffff/ffff{end}")),
+                Some(Background::Light),
+                "{end:?}"
+            );
+            assert_eq!(
+                from_osc11(&format!("\x1b]11;rgb:00/00/00{end}")),
+                Some(Background::Dark),
+                "{end:?}"
+            );
+        }
+        assert_eq!(from_osc11("\x1b]11;rgb:ff/ff\x07"), None);
+        assert_eq!(from_osc11("\x1b]11;?\x07"), None);
+    }
+
+    #[test]
+    fn osc11_reply_ends_at_the_terminator() {
+        let bel = b"\x1b]11;rgb:ffff/ffff/ffff\x07jk";
+        assert_eq!(reply_end(bel), Some(bel.len() - 3));
+        let st = b"\x1b]11;rgb:0000/0000/0000\x1b\\\x1b[A";
+        assert_eq!(reply_end(st), Some(st.len() - 5));
+        assert_eq!(reply_end(b"\x1b]11;rgb:0000/00"), None);
+        assert_eq!(reply_end(b"\x1b]11;rgb:0000/0000/0000\x1b"), None);
+    }
+}
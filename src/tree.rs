@@ -0,0 +1,30 @@
+use std::collections::{HashMap, HashSet};
+
+use sysinfo::{Pid, Process};
+
+/// Arrange `processes` as a forest, each process followed by its children.
+///
+/// Returns every process with its depth in the tree. Siblings keep their relative order
+/// from `processes`, so sorting beforehand sorts each level of the tree. Processes whose
+/// parent is not part of `processes` become roots.
+pub fn tree_order<'a>(processes: &[&'a Process]) -> Vec<(&'a Process, usize)> {
+    let pids: HashSet<Pid> = processes.iter().map(|process| process.pid()).collect();
+    let mut children: HashMap<Pid, Vec<&Process>> = HashMap::new();
+    let mut roots = vec![];
+    for &process in processes {
+        match process.parent().filter(|parent| pids.contains(parent)) {
+            Some(parent) => children.entry(parent).or_default().push(process),
+            None => roots.push(process),
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(processes.len());
+    let mut stack: Vec<(&Process, usize)> = roots.into_iter().rev().map(|p| (p, 0)).collect();
+    while let Some((process, depth)) = stack.pop() {
+        ordered.push((process, depth));
+        if let Some(kids) = children.get(&process.pid()) {
+            stack.extend(kids.iter().rev().map(|&kid| (kid, depth + 1)));
+        }
+    }
+    ordered
+}
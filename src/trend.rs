@@ -0,0 +1,43 @@
+//! Direction of each process's CPU usage since the previous refresh.
+
+use std::{cmp::Ordering, collections::HashMap};
+
+use sysinfo::{Pid, System};
+
+/// Changes smaller than this many percentage points count as steady, so rounding noise does
+/// not flip the arrows.
+const THRESHOLD: f32 = 1.0;
+
+/// CPU usage of every process at the last two refreshes.
+#[derive(Debug, Default)]
+pub struct CpuTrend {
+    previous: HashMap<Pid, f32>,
+    current: HashMap<Pid, f32>,
+}
+
+impl CpuTrend {
+    /// Record the CPU usage of all processes, after a refresh.
+    pub fn sample(&mut self, system: &System) {
+        self.previous = std::mem::take(&mut self.current);
+        self.current = system
+            .processes()
+            .iter()
+            .map(|(&pid, process)| (pid, process.cpu_usage()))
+            .collect();
+    }
+
+    /// Is the CPU usage of the process rising, falling or steady? `None` for processes
+    /// new since the previous refresh.
+    pub fn direction(&self, pid: Pid) -> Option<Ordering> {
+        let before = self.previous.get(&pid)?;
+        let now = self.current.get(&pid)?;
+        let change = now - before;
+        Some(if change >= THRESHOLD {
+            Ordering::Greater
+        } else if change <= -THRESHOLD {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        })
+    }
+}
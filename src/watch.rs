@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use sysinfo::{Pid, System};
+
+use crate::history::{History, Retention};
+
+/// CPU and memory history of the processes given with `--pid`.
+#[derive(Debug, Default)]
+pub struct Watch {
+    /// Chart series per watched process that is still alive.
+    pub history: BTreeMap<Pid, ProcessHistory>,
+    /// Watched processes that have exited.
+    pub exited: Vec<Pid>,
+}
+
+#[derive(Debug, Default)]
+pub struct ProcessHistory {
+    /// CPU usage in percent.
+    pub cpu: History,
+    /// Resident memory in MiB.
+    pub memory: History,
+}
+
+impl ProcessHistory {
+    fn new(retention: Retention) -> Self {
+        Self {
+            cpu: History::new(retention),
+            memory: History::new(retention),
+        }
+    }
+}
+
+impl Watch {
+    pub fn new(pids: &[Pid], retention: Retention) -> Self {
+        Self {
+            history: pids
+                .iter()
+                .map(|&pid| (pid, ProcessHistory::new(retention)))
+                .collect(),
+            exited: vec![],
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.history.is_empty() || !self.exited.is_empty()
+    }
+
+    /// Is `pid` one of the watched processes?
+    pub fn contains(&self, pid: Pid) -> bool {
+        self.history.contains_key(&pid) || self.exited.contains(&pid)
+    }
+
+    /// Record a sample of every watched process. Returns the processes that exited since
+    /// the last sample.
+    pub fn sample(&mut self, system: &System, x: f64) -> Vec<Pid> {
+        let mut exited = vec![];
+        self.history
+            .retain(|&pid, history| match system.process(pid) {
+                Some(process) => {
+                    history.cpu.push(x, process.cpu_usage() as f64);
+                    history
+                        .memory
+                        .push(x, process.memory() as f64 / (1024.0 * 1024.0));
+                    true
+                }
+                None => {
+                    exited.push(pid);
+                    false
+                }
+            });
+        self.exited.extend(&exited);
+        exited
+    }
+
+    /// Have all watched processes exited?
+    pub fn all_exited(&self) -> bool {
+        self.history.is_empty() && !self.exited.is_empty()
+    }
+
+    /// Highest memory usage of all series, for the chart bounds.
+    pub fn peak_memory(&self) -> f64 {
+        self.history
+            .values()
+            .flat_map(|history| history.memory.points().iter().map(|&(_, memory)| memory))
+            .fold(1.0, f64::max)
+    }
+
+    /// First and last x coordinate of all series, for the chart bounds.
+    pub fn x_bounds(&self) -> [f64; 2] {
+        self.history
+            .values()
+            .map(|history| history.cpu.x_bounds())
+            .reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])])
+            .unwrap_or_default()
+    }
+}
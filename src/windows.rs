@@ -0,0 +1,219 @@
+//! Win32 process details and actions sysinfo does not cover: services, handle counts and
+//! priority classes.
+
+use std::{collections::HashMap, fmt, io, ptr};
+
+use sysinfo::Pid;
+use windows_sys::Win32::{
+    Foundation::{CloseHandle, HANDLE},
+    System::{
+        Services::{
+            CloseServiceHandle, EnumServicesStatusExW, OpenSCManagerW,
+            ENUM_SERVICE_STATUS_PROCESSW, SC_ENUM_PROCESS_INFO, SC_MANAGER_ENUMERATE_SERVICE,
+            SERVICE_ACTIVE, SERVICE_WIN32,
+        },
+        Threading::{
+            GetPriorityClass, GetProcessHandleCount, OpenProcess, SetPriorityClass,
+            TerminateProcess, ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS,
+            HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS,
+            PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_INFORMATION, PROCESS_TERMINATE,
+            REALTIME_PRIORITY_CLASS,
+        },
+    },
+};
+
+/// Scheduling priority class of a process, the Windows counterpart of the nice value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityClass {
+    Idle,
+    BelowNormal,
+    Normal,
+    AboveNormal,
+    High,
+    /// Preempts even system threads. Needs administrator rights.
+    RealTime,
+}
+
+impl PriorityClass {
+    /// Lowest to highest.
+    pub const ALL: [PriorityClass; 6] = [
+        PriorityClass::Idle,
+        PriorityClass::BelowNormal,
+        PriorityClass::Normal,
+        PriorityClass::AboveNormal,
+        PriorityClass::High,
+        PriorityClass::RealTime,
+    ];
+
+    fn flag(self) -> u32 {
+        match self {
+            PriorityClass::Idle => IDLE_PRIORITY_CLASS,
+            PriorityClass::BelowNormal => BELOW_NORMAL_PRIORITY_CLASS,
+            PriorityClass::Normal => NORMAL_PRIORITY_CLASS,
+            PriorityClass::AboveNormal => ABOVE_NORMAL_PRIORITY_CLASS,
+            PriorityClass::High => HIGH_PRIORITY_CLASS,
+            PriorityClass::RealTime => REALTIME_PRIORITY_CLASS,
+        }
+    }
+
+    /// The next class up (`raise`) or down, staying at the ends.
+    pub fn step(self, raise: bool) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|&class| class == self)
+            .unwrap_or(2);
+        let index = if raise {
+            (index + 1).min(Self::ALL.len() - 1)
+        } else {
+            index.saturating_sub(1)
+        };
+        Self::ALL[index]
+    }
+}
+
+impl fmt::Display for PriorityClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PriorityClass::Idle => "idle",
+            PriorityClass::BelowNormal => "below normal",
+            PriorityClass::Normal => "normal",
+            PriorityClass::AboveNormal => "above normal",
+            PriorityClass::High => "high",
+            PriorityClass::RealTime => "realtime",
+        })
+    }
+}
+
+/// A process handle, closed when dropped.
+struct Process(HANDLE);
+
+impl Process {
+    fn open(pid: Pid, access: u32) -> io::Result<Self> {
+        // SAFETY: OpenProcess only takes plain values and returns null on failure.
+        let handle = unsafe { OpenProcess(access, 0, pid.as_u32()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self(handle))
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        // SAFETY: the handle came from OpenProcess and is closed only here.
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+/// Number of kernel object handles the process holds open.
+pub fn handle_count(pid: Pid) -> Option<u64> {
+    let process = Process::open(pid, PROCESS_QUERY_LIMITED_INFORMATION).ok()?;
+    let mut count = 0u32;
+    // SAFETY: the handle is valid and `count` outlives the call.
+    let ok = unsafe { GetProcessHandleCount(process.0, &mut count) };
+    (ok != 0).then_some(u64::from(count))
+}
+
+/// End the process like Task Manager does; Windows has no polite termination signal.
+pub fn terminate(pid: Pid) -> io::Result<()> {
+    let process = Process::open(pid, PROCESS_TERMINATE)?;
+    // SAFETY: the handle is valid and was opened with PROCESS_TERMINATE.
+    if unsafe { TerminateProcess(process.0, 1) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+pub fn priority_class(pid: Pid) -> io::Result<PriorityClass> {
+    let process = Process::open(pid, PROCESS_QUERY_LIMITED_INFORMATION)?;
+    // SAFETY: the handle is valid.
+    let flag = unsafe { GetPriorityClass(process.0) };
+    if flag == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    PriorityClass::ALL
+        .into_iter()
+        .find(|class| class.flag() == flag)
+        .ok_or_else(|| io::ErrorKind::InvalidData.into())
+}
+
+pub fn set_priority_class(pid: Pid, class: PriorityClass) -> io::Result<()> {
+    let process = Process::open(pid, PROCESS_SET_INFORMATION)?;
+    // SAFETY: the handle is valid and was opened with PROCESS_SET_INFORMATION.
+    if unsafe { SetPriorityClass(process.0, class.flag()) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// The running Win32 services by the process hosting them. Processes hosting several
+/// services, like `svchost.exe`, get their names joined by commas.
+pub fn services() -> HashMap<Pid, String> {
+    let mut services: HashMap<Pid, Vec<String>> = HashMap::new();
+    // SAFETY: the manager handle is closed below, and the entries are only read within the
+    // buffer EnumServicesStatusExW filled.
+    unsafe {
+        let manager = OpenSCManagerW(ptr::null(), ptr::null(), SC_MANAGER_ENUMERATE_SERVICE);
+        if manager.is_null() {
+            return HashMap::new();
+        }
+        // u64 elements keep the entries, which start with pointers, aligned.
+        let mut buffer: Vec<u64> = vec![];
+        let mut needed = 0u32;
+        let mut returned = 0u32;
+        let mut resume = 0u32;
+        loop {
+            let ok = EnumServicesStatusExW(
+                manager,
+                SC_ENUM_PROCESS_INFO,
+                SERVICE_WIN32,
+                SERVICE_ACTIVE,
+                buffer.as_mut_ptr().cast(),
+                (buffer.len() * 8) as u32,
+                &mut needed,
+                &mut returned,
+                &mut resume,
+                ptr::null(),
+            );
+            let entries = std::slice::from_raw_parts(
+                buffer.as_ptr().cast::<ENUM_SERVICE_STATUS_PROCESSW>(),
+                returned as usize,
+            );
+            for entry in entries {
+                let pid = entry.ServiceStatusProcess.dwProcessId;
+                if pid != 0 {
+                    services
+                        .entry(Pid::from_u32(pid))
+                        .or_default()
+                        .push(wide_string(entry.lpServiceName));
+                }
+            }
+            // More data is pending while the call fails and asks for a bigger buffer.
+            if ok != 0 || needed == 0 {
+                break;
+            }
+            buffer = vec![0; (needed as usize).div_ceil(8)];
+        }
+        CloseServiceHandle(manager);
+    }
+    services
+        .into_iter()
+        .map(|(pid, mut names)| {
+            names.sort();
+            (pid, names.join(","))
+        })
+        .collect()
+}
+
+/// Copy a null-terminated UTF-16 string.
+///
+/// # Safety
+///
+/// `text` must be null or point to a null-terminated string.
+unsafe fn wide_string(text: *const u16) -> String {
+    if text.is_null() {
+        return String::new();
+    }
+    let len = (0..).take_while(|&i| *text.add(i) != 0).count();
+    String::from_utf16_lossy(std::slice::from_raw_parts(text, len))
+}